@@ -1,12 +1,30 @@
 use std::io::prelude::*;
-use std::net::TcpStream;
+use std::net::{Shutdown, TcpStream};
+
+use server::{HttpSchemeEnum, Uri};
 
 fn main() -> std::io::Result<()> {
     let mut stream = TcpStream::connect("127.0.0.1:8080")?;
 
-    let string = "https://telemakos.io/test?test_query";
-    println!("Sending {} . . .", string);
-    stream.write_all(string.as_bytes())?;
-    // stream.read(&mut [0; 128])?;
+    let uri = Uri::builder()
+        .scheme(HttpSchemeEnum::HTTPS)
+        .host("telemakos.io")
+        .path_segment("test")
+        .query_pair("test_query", "")
+        .build()
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid URI"))?;
+    println!("Sending {} . . .", uri);
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\n\r\n",
+        uri.request_target(),
+        uri.host()
+    );
+    stream.write_all(request.as_bytes())?;
+    // The server answers until the client closes its side.
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    print!("{}", response);
     Ok(())
 }
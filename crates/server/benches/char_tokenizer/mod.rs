@@ -0,0 +1,420 @@
+//! A frozen copy of `Tokenizer` as it was before `ByteTokenizer`, which
+//! copied the buffer into a `Vec<char>` on every call to `next`. It is only
+//! kept so the benchmarks have the old implementation to compare against.
+
+use server::{LocalState, Location, Tag, Token, UriError};
+
+#[derive(Clone)]
+pub struct CharTokenizer {
+    buffer: String,
+    index: usize,
+    processing_tag: Tag,
+}
+
+impl CharTokenizer {
+    pub fn new(buffer: String) -> Self {
+        Self {
+            buffer,
+            index: 0,
+            processing_tag: Tag::Start,
+        }
+    }
+
+    /// The character starting at the current index, for error reports.
+    fn found(&self) -> char {
+        self.found_at(self.index)
+    }
+
+    fn found_at(&self, offset: usize) -> char {
+        self.buffer
+            .get(offset..)
+            .and_then(|rest| rest.chars().next())
+            .unwrap_or_default()
+    }
+
+    fn invalid_char(&self, component: Tag) -> UriError {
+        UriError::InvalidChar {
+            offset: self.index,
+            found: self.found(),
+            component,
+        }
+    }
+
+    pub fn tokens(&mut self) -> Result<Vec<Token>, UriError> {
+        let mut tokens: Vec<Token> = vec![];
+        self.index = 0;
+        self.processing_tag = Tag::Start;
+
+        loop {
+            match self.next() {
+                Ok(token) => tokens.push(token),
+                Err(e) => return Err(e),
+            };
+
+            // tokens.push(next);
+            if self.processing_tag == Tag::End {
+                break;
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Token, UriError> {
+        let mut result = Token::new(
+            self.processing_tag,
+            Location::new(self.index, self.index + 1),
+        );
+        let mut local_state = LocalState::InToken;
+
+        // One entry per byte, so indices are byte offsets that slice the
+        // buffer directly. Bytes of a multi-byte character are never valid
+        // outside the query.
+        let buffer_as_chars: Vec<char> = self.buffer.bytes().map(char::from).collect();
+
+        while local_state != LocalState::EndOfToken {
+            if self.index == buffer_as_chars.len() {
+                result.set_end(self.index);
+                self.processing_tag = Tag::End;
+
+                return Ok(result);
+            }
+
+            match self.processing_tag {
+                // A relative reference has no scheme, so the first section
+                // is picked from the leading characters. The scheme itself
+                // is validated by the Tag::Scheme arm.
+                Tag::Start => {
+                    let next_tag = if has_scheme(&buffer_as_chars) {
+                        Tag::Scheme
+                    } else {
+                        match buffer_as_chars[self.index] {
+                            // network-path reference; skip the "//"
+                            '/' if buffer_as_chars.get(self.index + 1) == Some(&'/') => {
+                                self.index += 2;
+                                if has_user_info(&buffer_as_chars, self.index) {
+                                    Tag::UserInfo
+                                } else {
+                                    Tag::Authority
+                                }
+                            }
+                            // absolute-path reference; the path token starts
+                            // after the '/' as it does after an authority.
+                            '/' => {
+                                self.index += 1;
+                                Tag::Path
+                            }
+                            '?' => {
+                                self.index += 1;
+                                Tag::Query
+                            }
+                            '#' => {
+                                self.index += 1;
+                                Tag::Fragment
+                            }
+                            // relative-path reference
+                            _ => Tag::Path,
+                        }
+                    };
+                    result = Token::new(next_tag, Location::new(self.index, self.index + 1));
+                    self.processing_tag = next_tag;
+                }
+                Tag::Scheme => match buffer_as_chars[self.index] {
+                    // Valid characters; continue
+                    'h' | 't' | 'p' | 's' => self.index += 1,
+                    // Post-delimiter; set result tag to current section,
+                    // result end location to current index, local state
+                    // to EndOfToken, Tag to the next section
+                    // (Authority)
+                    ':' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        // http(s) URIs always carry an authority, so "//"
+                        // must follow the ':'. Check before skipping it so
+                        // the index never runs past the buffer.
+                        for offset in self.index + 1..self.index + 3 {
+                            match buffer_as_chars.get(offset) {
+                                Some('/') => continue,
+                                Some(_) => {
+                                    return Err(UriError::InvalidChar {
+                                        offset,
+                                        found: self.found_at(offset),
+                                        component: Tag::Authority,
+                                    })
+                                }
+                                None => return Err(UriError::UnexpectedEnd { offset }),
+                            }
+                        }
+                        // skip ://
+                        self.index += 3;
+                        // A userinfo subcomponent can only be told apart from
+                        // a host and port by the '@' that ends it, so look
+                        // ahead for one before the authority is over.
+                        self.processing_tag = if has_user_info(&buffer_as_chars, self.index) {
+                            Tag::UserInfo
+                        } else {
+                            Tag::Authority
+                        };
+                    }
+                    // Non valid characters; report where the scheme went
+                    // wrong.
+                    _ => {
+                        return Err(UriError::InvalidScheme {
+                            offset: self.index,
+                            found: self.found(),
+                        });
+                    }
+                },
+                Tag::UserInfo => match buffer_as_chars[self.index] {
+                    // Post-delimiter; the host follows.
+                    '@' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        self.processing_tag = Tag::Authority;
+                        self.index += 1;
+                    }
+                    // userinfo = *( unreserved / pct-encoded / sub-delims / ":" )
+                    'A'..='Z'
+                    | 'a'..='z'
+                    | '0'..='9'
+                    | '-'
+                    | '.'
+                    | '_'
+                    | '~'
+                    | '!'
+                    | '$'
+                    | '&'
+                    | '\''
+                    | '('
+                    | ')'
+                    | '*'
+                    | '+'
+                    | ','
+                    | ';'
+                    | '='
+                    | ':' => self.index += 1,
+                    '%' if is_pct_encoded(&buffer_as_chars, self.index) => self.index += 3,
+                    '%' => return Err(UriError::InvalidPercentEscape { offset: self.index }),
+                    _ => return Err(self.invalid_char(Tag::UserInfo)),
+                },
+                Tag::Authority => match buffer_as_chars[self.index] {
+                    // IP-literal; the ':' separators inside the brackets
+                    // belong to the address, not the port. Host::parse
+                    // validates the contents.
+                    '[' if self.index == result.location().start() => {
+                        match buffer_as_chars[self.index..].iter().position(|c| *c == ']') {
+                            Some(offset) => self.index += offset + 1,
+                            None => {
+                                return Err(UriError::UnexpectedEnd {
+                                    offset: buffer_as_chars.len(),
+                                })
+                            }
+                        }
+                    }
+                    ':' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        self.processing_tag = Tag::Port;
+                        self.index += 1;
+                    }
+                    '/' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        self.processing_tag = Tag::Path;
+                        self.index += 1;
+                    }
+                    '?' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        self.processing_tag = Tag::Query;
+                        self.index += 1;
+                    }
+                    '#' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        self.processing_tag = Tag::Fragment;
+                        self.index += 1;
+                    }
+                    '_'
+                    | 'A'..='Z'
+                    | 'a'..='z'
+                    | '0'..='9'
+                    | '-'
+                    | '.'
+                    | '~'
+                    | '!'
+                    | '$'
+                    | '&'
+                    | '\''
+                    | '('
+                    | ')'
+                    | '*'
+                    | '+'
+                    | ','
+                    | ';'
+                    | '=' => self.index += 1,
+                    '%' if is_pct_encoded(&buffer_as_chars, self.index) => self.index += 3,
+                    '%' => return Err(UriError::InvalidPercentEscape { offset: self.index }),
+                    _ => {
+                        return Err(UriError::InvalidHostChar {
+                            offset: self.index,
+                            found: self.found(),
+                        })
+                    }
+                },
+                Tag::Port => match buffer_as_chars[self.index] {
+                    // Ports are only valid unsigned ints.
+                    '0'..='9' => self.index += 1,
+                    // Pre-delimiter. '/' begins the path portion. set this
+                    // token as port and prepare for path.
+                    '/' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        self.processing_tag = Tag::Path;
+                        self.index += 1;
+                    }
+                    '?' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        self.processing_tag = Tag::Query;
+                        self.index += 1;
+                    }
+                    '#' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        self.processing_tag = Tag::Fragment;
+                        self.index += 1;
+                    }
+                    _ => return Err(self.invalid_char(Tag::Port)),
+                },
+                Tag::Path => match buffer_as_chars[self.index] {
+                    // Pre-delimiter. send path and prepare for query.
+                    '?' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        self.processing_tag = Tag::Query;
+                        self.index += 1;
+                    }
+                    // If no '?', the check for fragment. Pre-delimiter, send
+                    // path and prep fragment.
+                    '#' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        self.processing_tag = Tag::Fragment;
+                        self.index += 1;
+                    }
+                    // path-abempty = *( "/" segment ), segment = *pchar
+                    'A'..='Z'
+                    | 'a'..='z'
+                    | '0'..='9'
+                    | '-'
+                    | '.'
+                    | '_'
+                    | '~'
+                    | '!'
+                    | '$'
+                    | '&'
+                    | '\''
+                    | '('
+                    | ')'
+                    | '*'
+                    | '+'
+                    | ','
+                    | ';'
+                    | '='
+                    | ':'
+                    | '@'
+                    | '/' => self.index += 1,
+                    '%' if is_pct_encoded(&buffer_as_chars, self.index) => self.index += 3,
+                    '%' => return Err(UriError::InvalidPercentEscape { offset: self.index }),
+                    _ => return Err(self.invalid_char(Tag::Path)),
+                },
+                Tag::Query => match buffer_as_chars[self.index] {
+                    // If fragment, send query, prep frag.
+                    '#' => {
+                        result.set_end(self.index);
+                        local_state = LocalState::EndOfToken;
+                        self.processing_tag = Tag::Fragment;
+                        self.index += 1;
+                    }
+                    // Same thing; when I feel like it.
+                    _ => self.index += 1,
+                },
+
+                // fragment = *( pchar / "/" / "?" )
+                Tag::Fragment => match buffer_as_chars[self.index] {
+                    'A'..='Z'
+                    | 'a'..='z'
+                    | '0'..='9'
+                    | '-'
+                    | '.'
+                    | '_'
+                    | '~'
+                    | '!'
+                    | '$'
+                    | '&'
+                    | '\''
+                    | '('
+                    | ')'
+                    | '*'
+                    | '+'
+                    | ','
+                    | ';'
+                    | '='
+                    | ':'
+                    | '@'
+                    | '/'
+                    | '?' => self.index += 1,
+                    '%' if is_pct_encoded(&buffer_as_chars, self.index) => self.index += 3,
+                    '%' => return Err(UriError::InvalidPercentEscape { offset: self.index }),
+                    _ => return Err(self.invalid_char(Tag::Fragment)),
+                },
+                // The index check at the top of the loop returns before
+                // anything past the end is read, and no arm moves into
+                // Invalid. Should either be reached, finish the token.
+                Tag::End | Tag::Invalid => self.index = buffer_as_chars.len(),
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." ) ":"
+///
+/// A ':' in the first segment of a relative-path reference would be read as
+/// a scheme, so RFC 3986 §4.2 forbids it there.
+fn has_scheme(chars: &[char]) -> bool {
+    match chars.first() {
+        Some(c) if c.is_ascii_alphabetic() => {}
+        _ => return false,
+    }
+    for c in chars.iter().skip(1) {
+        match c {
+            ':' => return true,
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '+' | '-' | '.' => continue,
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Scans forward from the start of the authority for an '@' that closes a
+/// userinfo subcomponent. The authority ends at the first '/', '?' or '#'.
+fn has_user_info(chars: &[char], start: usize) -> bool {
+    for c in chars.iter().skip(start) {
+        match c {
+            '@' => return true,
+            '/' | '?' | '#' => return false,
+            _ => continue,
+        }
+    }
+    false
+}
+
+/// pct-encoded = "%" HEXDIG HEXDIG, starting at `index`.
+fn is_pct_encoded(chars: &[char], index: usize) -> bool {
+    chars.len() > index + 2
+        && chars[index + 1].is_ascii_hexdigit()
+        && chars[index + 2].is_ascii_hexdigit()
+}
@@ -0,0 +1,74 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use server::{ByteTokenizer, Tokenizer, Uri, UriRef};
+
+mod char_tokenizer;
+
+use char_tokenizer::CharTokenizer;
+
+/// notes.md: "Must support URI's of 8000 octets".
+const LENGTHS: [usize; 3] = [64, 1024, 8000];
+
+/// An absolute URI of exactly `length` octets, mostly path with a short
+/// query and fragment.
+fn uri_of_length(length: usize) -> String {
+    let head = "https://user@telemakos.io:8443";
+    let tail = "?key=value&page=2#top";
+    let mut uri = String::from(head);
+    while uri.len() + tail.len() < length {
+        uri.push_str("/segment");
+    }
+    uri.truncate(length - tail.len());
+    uri.push_str(tail);
+    uri
+}
+
+fn tokenize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tokenize");
+    for length in LENGTHS {
+        let input = uri_of_length(length);
+        group.throughput(Throughput::Bytes(length as u64));
+
+        // The char-based tokenizer took an owned String, so its clone is
+        // part of what it cost.
+        group.bench_with_input(
+            BenchmarkId::new("CharTokenizer", length),
+            &input,
+            |b, input| {
+                b.iter(|| {
+                    let mut tokenizer = CharTokenizer::new(input.clone());
+                    black_box(tokenizer.tokens().ok())
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("ByteTokenizer", length),
+            &input,
+            |b, input| b.iter(|| ByteTokenizer::new(black_box(input.as_bytes())).count()),
+        );
+    }
+    group.finish();
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for length in LENGTHS {
+        let input = uri_of_length(length);
+        group.throughput(Throughput::Bytes(length as u64));
+
+        group.bench_with_input(BenchmarkId::new("Uri", length), &input, |b, input| {
+            b.iter(|| {
+                let mut tokenizer = Tokenizer::new(input.clone());
+                black_box(Uri::parse_tokens(&mut tokenizer).ok())
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("UriRef", length), &input, |b, input| {
+            b.iter(|| black_box(UriRef::parse(black_box(input.as_bytes())).ok()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tokenize, parse);
+criterion_main!(benches);
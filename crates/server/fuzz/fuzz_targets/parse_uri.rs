@@ -0,0 +1,26 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use server::{percent_decode, Host, Query, Tokenizer, Uri};
+
+// Run with `cargo +nightly fuzz run parse_uri` from crates/server.
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data).into_owned();
+
+    let mut tokenizer = Tokenizer::new(input.clone());
+    let _ = tokenizer.to_string();
+    let _ = Host::parse(&input);
+    let _ = Query::parse(&input);
+    let _ = percent_decode(&input);
+
+    if let Ok(uri) = Uri::parse_tokens(&mut tokenizer) {
+        // Anything that parses must serialize to something that parses back
+        // to the same value.
+        let mut reparsed = Tokenizer::new(uri.to_string());
+        assert_eq!(Uri::parse_tokens(&mut reparsed).ok(), Some(uri.clone()));
+
+        let _ = uri.normalize();
+        let _ = uri.query_pairs();
+        let _ = uri.join(&input);
+    }
+});
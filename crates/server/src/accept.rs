@@ -0,0 +1,605 @@
+use crate::header::request;
+use crate::media::Cursor;
+use crate::{char_at, HeaderError, HeaderMap, HeaderName, MediaType};
+
+/// The highest q-value, in thousandths.
+const MAX_QUALITY: u16 = 1000;
+
+/// An entry of an Accept list and its weight.
+///
+/// Quality is the q-value in thousandths, so `q=0.5` is 500. Zero means
+/// "not acceptable".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QualityItem<T> {
+    item: T,
+    quality: u16,
+}
+
+impl<T> QualityItem<T> {
+    pub fn item(&self) -> &T {
+        &self.item
+    }
+
+    pub fn quality(&self) -> u16 {
+        self.quality
+    }
+}
+
+/// The Accept field, RFC 9110 §12.5.1: the media ranges a client takes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Accept {
+    ranges: Vec<QualityItem<MediaType>>,
+}
+
+impl Accept {
+    /// Accept = #( media-range [ weight ] )
+    ///
+    /// Parameters after the weight are accept extensions and are dropped.
+    pub fn parse(input: &str) -> Result<Self, HeaderError> {
+        let ranges = parse_list(input, |cursor| {
+            let start = cursor.index();
+            let (range, weighted) = cursor.media_type(|name| name == "q")?;
+            // "*/html" is not a media range.
+            if range.main_type() == "*" && range.subtype() != "*" {
+                return Err(HeaderError::InvalidChar {
+                    offset: start + 2,
+                    found: char_at(range.subtype().as_bytes(), 0),
+                });
+            }
+            let quality = match weighted {
+                true => weight_param(cursor)?,
+                false => MAX_QUALITY,
+            };
+            skip_extensions(cursor)?;
+            Ok(QualityItem {
+                item: range,
+                quality,
+            })
+        })?;
+        Ok(Self { ranges })
+    }
+
+    /// Reads every Accept field of a message. Without one, any media type
+    /// is acceptable.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, HeaderError> {
+        match joined(headers, request::ACCEPT)? {
+            Some(value) => Self::parse(&value),
+            None => Self::parse("*/*"),
+        }
+    }
+
+    /// The ranges from most to least preferred. Ties keep the order sent.
+    pub fn ranges(&self) -> &[QualityItem<MediaType>] {
+        &self.ranges
+    }
+
+    /// The weight of the most specific range matching `media_type`, RFC
+    /// 9110 §12.5.1: `text/html;level=1` over `text/html` over `text/*`
+    /// over `*/*`.
+    pub fn quality(&self, media_type: &MediaType) -> u16 {
+        let specificity = |range: &MediaType| match (range.main_type(), range.subtype()) {
+            ("*", "*") => Some(0),
+            (main, "*") if main == media_type.main_type() => Some(1),
+            _ if range.essence_eq(media_type) => {
+                let matched = range.params().all(|(name, value)| {
+                    media_type
+                        .param(name)
+                        .is_some_and(|v| v.eq_ignore_ascii_case(value))
+                });
+                matched.then(|| 2 + range.params().count())
+            }
+            _ => None,
+        };
+        best_match(&self.ranges, specificity)
+    }
+
+    /// Picks the available media type the client prefers. Ties go to the
+    /// one listed first, so `available` is the server's order of
+    /// preference. `None` when nothing is acceptable, which a handler can
+    /// answer with 406 or with its default.
+    pub fn negotiate<'a>(&self, available: &'a [MediaType]) -> Option<&'a MediaType> {
+        negotiate(available, |m| self.quality(m))
+    }
+}
+
+/// The Accept-Language field, RFC 9110 §12.5.4, matched by RFC 4647 basic
+/// filtering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptLanguage {
+    ranges: Vec<QualityItem<String>>,
+}
+
+impl AcceptLanguage {
+    /// Accept-Language = #( language-range [ weight ] )
+    ///
+    /// language-range = ( 1*8ALPHA *( "-" 1*8alphanum ) ) / "*"
+    pub fn parse(input: &str) -> Result<Self, HeaderError> {
+        let ranges = parse_tokens(input, |range, offset| {
+            if range == "*" {
+                return Ok(());
+            }
+            let mut subtag_start = 0;
+            for (i, subtag) in range.split('-').enumerate() {
+                let valid = match i {
+                    0 => subtag.bytes().all(|b| b.is_ascii_alphabetic()),
+                    _ => subtag.bytes().all(|b| b.is_ascii_alphanumeric()),
+                };
+                if !valid || subtag.is_empty() || subtag.len() > 8 {
+                    return Err(HeaderError::InvalidChar {
+                        offset: offset + subtag_start,
+                        found: char_at(range.as_bytes(), subtag_start),
+                    });
+                }
+                subtag_start += subtag.len() + 1;
+            }
+            Ok(())
+        })?;
+        Ok(Self { ranges })
+    }
+
+    /// Reads every Accept-Language field of a message. Without one, any
+    /// language is acceptable.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, HeaderError> {
+        match joined(headers, request::ACCEPT_LANGUAGE)? {
+            Some(value) => Self::parse(&value),
+            None => Self::parse("*"),
+        }
+    }
+
+    pub fn ranges(&self) -> &[QualityItem<String>] {
+        &self.ranges
+    }
+
+    /// The weight of the longest range that is `tag` or a prefix of it
+    /// ending at a '-', so `en` matches `en-US`.
+    pub fn quality(&self, tag: &str) -> u16 {
+        best_match(&self.ranges, |range: &String| match range.as_str() {
+            "*" => Some(0),
+            range if tag.eq_ignore_ascii_case(range) => Some(range.len()),
+            range
+                if tag.len() > range.len()
+                    && tag.as_bytes()[range.len()] == b'-'
+                    && tag[..range.len()].eq_ignore_ascii_case(range) =>
+            {
+                Some(range.len())
+            }
+            _ => None,
+        })
+    }
+
+    /// Picks the available language tag the client prefers. Ties go to
+    /// the one listed first.
+    pub fn negotiate<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        negotiate(available, |tag| self.quality(tag)).copied()
+    }
+}
+
+/// The Accept-Charset field, RFC 9110 §12.5.2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptCharset {
+    charsets: Vec<QualityItem<String>>,
+}
+
+impl AcceptCharset {
+    /// Accept-Charset = #( ( token / "*" ) [ weight ] )
+    pub fn parse(input: &str) -> Result<Self, HeaderError> {
+        let charsets = parse_tokens(input, |_, _| Ok(()))?;
+        Ok(Self { charsets })
+    }
+
+    /// Reads every Accept-Charset field of a message. Without one, any
+    /// charset is acceptable.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, HeaderError> {
+        match joined(headers, request::ACCEPT_CHARSET)? {
+            Some(value) => Self::parse(&value),
+            None => Self::parse("*"),
+        }
+    }
+
+    pub fn charsets(&self) -> &[QualityItem<String>] {
+        &self.charsets
+    }
+
+    pub fn quality(&self, charset: &str) -> u16 {
+        best_match(&self.charsets, |c: &String| match c.as_str() {
+            "*" => Some(0),
+            c if c.eq_ignore_ascii_case(charset) => Some(1),
+            _ => None,
+        })
+    }
+
+    /// Picks the available charset the client prefers. Ties go to the one
+    /// listed first.
+    pub fn negotiate<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        negotiate(available, |charset| self.quality(charset)).copied()
+    }
+}
+
+/// The Accept-Encoding field, RFC 9110 §12.5.3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptEncoding {
+    codings: Vec<QualityItem<String>>,
+}
+
+impl AcceptEncoding {
+    /// Accept-Encoding = #( codings [ weight ] )
+    ///
+    /// codings = content-coding / "identity" / "*"
+    ///
+    /// An empty value is valid and means only identity is acceptable.
+    pub fn parse(input: &str) -> Result<Self, HeaderError> {
+        let codings = parse_tokens(input, |_, _| Ok(()))?;
+        Ok(Self { codings })
+    }
+
+    /// Reads every Accept-Encoding field of a message. Without one, any
+    /// coding is acceptable.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, HeaderError> {
+        match joined(headers, request::ACCEPT_ENCODING)? {
+            Some(value) => Self::parse(&value),
+            None => Self::parse("*"),
+        }
+    }
+
+    pub fn codings(&self) -> &[QualityItem<String>] {
+        &self.codings
+    }
+
+    /// The weight of a content coding. `identity` stays acceptable unless
+    /// it, or `*` with no `identity` entry, is given q=0.
+    pub fn quality(&self, coding: &str) -> u16 {
+        let matches = |c: &String| match c.as_str() {
+            "*" => Some(0),
+            c if c.eq_ignore_ascii_case(coding) => Some(1),
+            _ => None,
+        };
+        match self.codings.iter().any(|c| matches(&c.item).is_some()) {
+            false if coding.eq_ignore_ascii_case("identity") => MAX_QUALITY,
+            _ => best_match(&self.codings, matches),
+        }
+    }
+
+    /// Picks the available coding the client prefers. Ties go to the one
+    /// listed first.
+    pub fn negotiate<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        negotiate(available, |coding| self.quality(coding)).copied()
+    }
+}
+
+/// The quality of the entry `specificity` ranks highest, or 0 when none
+/// match. Equal ranks keep the first entry.
+fn best_match<T>(items: &[QualityItem<T>], specificity: impl Fn(&T) -> Option<usize>) -> u16 {
+    let mut best: Option<(usize, u16)> = None;
+    for item in items {
+        if let Some(rank) = specificity(&item.item) {
+            if best.is_none_or(|(best_rank, _)| rank > best_rank) {
+                best = Some((rank, item.quality));
+            }
+        }
+    }
+    best.map_or(0, |(_, quality)| quality)
+}
+
+/// The acceptable candidate with the highest quality. Ties go to the
+/// earliest, so candidates are listed in the server's preference.
+fn negotiate<T>(available: &[T], quality: impl Fn(&T) -> u16) -> Option<&T> {
+    let mut best: Option<(&T, u16)> = None;
+    for candidate in available {
+        let q = quality(candidate);
+        if q > 0 && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((candidate, q));
+        }
+    }
+    best.map(|(candidate, _)| candidate)
+}
+
+/// Every value of a list field joined into one, or `None` without one.
+fn joined(headers: &HeaderMap, name: HeaderName) -> Result<Option<String>, HeaderError> {
+    let value = match headers.get_joined(name) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    match value.to_str() {
+        Some(text) => Ok(Some(String::from(text))),
+        None => {
+            let offset = value
+                .as_bytes()
+                .iter()
+                .position(|b| !b.is_ascii())
+                .unwrap_or(0);
+            Err(HeaderError::InvalidChar {
+                offset,
+                found: char_at(value.as_bytes(), offset),
+            })
+        }
+    }
+}
+
+/// Reads a comma-separated list, RFC 9110 §5.6.1. Empty elements are
+/// skipped, and the list is sorted from highest to lowest quality.
+///
+/// #element => [ element ] *( OWS "," OWS [ element ] )
+fn parse_list<T>(
+    input: &str,
+    mut element: impl FnMut(&mut Cursor<'_>) -> Result<QualityItem<T>, HeaderError>,
+) -> Result<Vec<QualityItem<T>>, HeaderError> {
+    let mut cursor = Cursor::new(input);
+    let mut items = vec![];
+    loop {
+        cursor.skip_ows();
+        if cursor.eat(b',') {
+            continue;
+        }
+        if cursor.is_end() {
+            break;
+        }
+        items.push(element(&mut cursor)?);
+        cursor.skip_ows();
+        if !cursor.is_end() {
+            cursor.expect(b',')?;
+        }
+    }
+    // A stable sort, so equal weights keep the order the client sent.
+    items.sort_by_key(|item| std::cmp::Reverse(item.quality));
+    Ok(items)
+}
+
+/// Reads a list of lowercased tokens, each with an optional weight.
+/// `check` gets each token and its offset for further grammar.
+fn parse_tokens(
+    input: &str,
+    check: impl Fn(&str, usize) -> Result<(), HeaderError>,
+) -> Result<Vec<QualityItem<String>>, HeaderError> {
+    parse_list(input, |cursor| {
+        let start = cursor.index();
+        let token = cursor.token()?;
+        check(token, start)?;
+
+        let before = cursor.index();
+        cursor.skip_ows();
+        let quality = match cursor.eat(b';') {
+            true => {
+                cursor.skip_ows();
+                let name_start = cursor.index();
+                if !cursor.token()?.eq_ignore_ascii_case("q") {
+                    return Err(HeaderError::InvalidChar {
+                        offset: name_start,
+                        found: char_at(input.as_bytes(), name_start),
+                    });
+                }
+                weight_value(cursor)?
+            }
+            false => {
+                cursor.reset(before);
+                MAX_QUALITY
+            }
+        };
+        Ok(QualityItem {
+            item: token.to_ascii_lowercase(),
+            quality,
+        })
+    })
+}
+
+/// accept-ext = OWS ";" OWS token [ "=" ( token / quoted-string ) ]
+fn skip_extensions(cursor: &mut Cursor<'_>) -> Result<(), HeaderError> {
+    loop {
+        let before = cursor.index();
+        cursor.skip_ows();
+        if !cursor.eat(b';') {
+            cursor.reset(before);
+            return Ok(());
+        }
+        cursor.skip_ows();
+        cursor.token()?;
+        if cursor.eat(b'=') {
+            cursor.param_value()?;
+        }
+    }
+}
+
+/// Reads the `q` name of a weight and its value.
+fn weight_param(cursor: &mut Cursor<'_>) -> Result<u16, HeaderError> {
+    cursor.token()?;
+    weight_value(cursor)
+}
+
+/// weight = OWS ";" OWS "q=" qvalue, from the '='.
+///
+/// qvalue = ( "0" [ "." 0*3DIGIT ] ) / ( "1" [ "." 0*3("0") ] )
+fn weight_value(cursor: &mut Cursor<'_>) -> Result<u16, HeaderError> {
+    cursor.expect(b'=')?;
+    let start = cursor.index();
+    let qvalue = cursor.token()?;
+    let invalid = HeaderError::InvalidQuality { offset: start };
+
+    let (whole, fraction) = match qvalue.split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (qvalue, ""),
+    };
+    if fraction.len() > 3 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid);
+    }
+    let thousandths = fraction
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(3)
+        .fold(0, |q, b| q * 10 + u16::from(b - b'0'));
+    match whole {
+        "0" => Ok(thousandths),
+        "1" if thousandths == 0 => Ok(MAX_QUALITY),
+        _ => Err(invalid),
+    }
+}
+
+#[test]
+fn test_accept_notes_frame() {
+    let accept = Accept::parse("text/html,application/xhtml+xml,")
+        .ok()
+        .unwrap();
+
+    assert_eq!(accept.ranges().len(), 2);
+    let html = MediaType::parse("text/html").ok().unwrap();
+    let json = MediaType::parse("application/json").ok().unwrap();
+    assert_eq!(accept.quality(&html), 1000);
+    assert_eq!(accept.quality(&json), 0);
+    assert_eq!(accept.negotiate(&[json, html.clone()]), Some(&html));
+}
+
+#[test]
+fn test_accept_specificity() {
+    // RFC 9110 §12.5.1.
+    let accept = Accept::parse(
+        "text/*;q=0.3, text/plain;q=0.7, text/plain;format=flowed, text/plain;format=fixed;q=0.4, */*;q=0.5",
+    )
+    .ok()
+    .unwrap();
+
+    let quality = |media_type: &str| accept.quality(&MediaType::parse(media_type).ok().unwrap());
+    assert_eq!(quality("text/plain;format=flowed"), 1000);
+    assert_eq!(quality("text/plain"), 700);
+    assert_eq!(quality("text/html"), 300);
+    assert_eq!(quality("image/jpeg"), 500);
+    assert_eq!(quality("text/plain;format=fixed"), 400);
+    assert_eq!(quality("text/html;level=3"), 300);
+
+    assert_eq!(
+        accept.ranges()[0].item().to_string(),
+        "text/plain;format=flowed"
+    );
+    assert_eq!(accept.ranges()[4].quality(), 300);
+}
+
+#[test]
+fn test_accept_negotiate_json_or_html() {
+    let html = MediaType::parse("text/html").ok().unwrap();
+    let json = MediaType::parse("application/json").ok().unwrap();
+    let available = [html.clone(), json.clone()];
+
+    let accept = Accept::parse("application/json, text/html;q=0.9")
+        .ok()
+        .unwrap();
+    assert_eq!(accept.negotiate(&available), Some(&json));
+
+    let accept = Accept::parse("*/*").ok().unwrap();
+    assert_eq!(accept.negotiate(&available), Some(&html));
+
+    let accept = Accept::parse("image/*, text/html;q=0").ok().unwrap();
+    assert_eq!(accept.negotiate(&available), None);
+}
+
+#[test]
+fn test_accept_errors() {
+    assert_eq!(
+        Accept::parse("text/html;q=1.5").err(),
+        Some(HeaderError::InvalidQuality { offset: 12 })
+    );
+    assert_eq!(
+        Accept::parse("text/html;q=0.1234").err(),
+        Some(HeaderError::InvalidQuality { offset: 12 })
+    );
+    assert_eq!(
+        Accept::parse("*/html").err(),
+        Some(HeaderError::InvalidChar {
+            offset: 2,
+            found: 'h'
+        })
+    );
+    assert_eq!(
+        Accept::parse("text/html text/plain").err(),
+        Some(HeaderError::InvalidChar {
+            offset: 10,
+            found: 't'
+        })
+    );
+}
+
+#[test]
+fn test_accept_quality_bounds() {
+    let accept = Accept::parse("a/a;q=1, b/b;q=1.000, c/c;q=0.999, d/d;q=0, e/e;q=0.")
+        .ok()
+        .unwrap();
+    let qualities: Vec<u16> = accept.ranges().iter().map(|r| r.quality()).collect();
+    assert_eq!(qualities, [1000, 1000, 999, 0, 0]);
+    for range in accept.ranges() {
+        assert!(range.quality() <= MAX_QUALITY);
+        let reparsed = MediaType::parse(&range.item().to_string()).ok().unwrap();
+        assert_eq!(&reparsed, range.item());
+    }
+
+    for input in ["a/a;q=1.001", "a/a;q=1.1", "a/a;q=2", "a/a;q=.5"] {
+        assert_eq!(
+            Accept::parse(input).err(),
+            Some(HeaderError::InvalidQuality { offset: 6 }),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_accept_language() {
+    let accept = AcceptLanguage::parse("da, en-gb;q=0.8, en;q=0.7")
+        .ok()
+        .unwrap();
+
+    assert_eq!(accept.quality("da"), 1000);
+    assert_eq!(accept.quality("en-GB"), 800);
+    assert_eq!(accept.quality("en-US"), 700);
+    assert_eq!(accept.quality("fr"), 0);
+    assert_eq!(accept.negotiate(&["fr", "en-US", "en-GB"]), Some("en-GB"));
+    assert_eq!(
+        AcceptLanguage::parse("en-verylongtag").err(),
+        Some(HeaderError::InvalidChar {
+            offset: 3,
+            found: 'v'
+        })
+    );
+}
+
+#[test]
+fn test_accept_charset() {
+    let accept = AcceptCharset::parse("ISO-8859-1,utf-8;q=0.7,*;q=0.3")
+        .ok()
+        .unwrap();
+
+    assert_eq!(accept.charsets()[0].item(), "iso-8859-1");
+    assert_eq!(accept.quality("UTF-8"), 700);
+    assert_eq!(accept.quality("shift_jis"), 300);
+    assert_eq!(
+        accept.negotiate(&["utf-8", "iso-8859-1"]),
+        Some("iso-8859-1")
+    );
+}
+
+#[test]
+fn test_accept_encoding() {
+    let accept = AcceptEncoding::parse("gzip,deflate").ok().unwrap();
+    assert_eq!(accept.quality("gzip"), 1000);
+    assert_eq!(accept.quality("br"), 0);
+    assert_eq!(accept.quality("identity"), 1000);
+    assert_eq!(accept.negotiate(&["br", "gzip"]), Some("gzip"));
+
+    let accept = AcceptEncoding::parse("br;q=1.0, *;q=0").ok().unwrap();
+    assert_eq!(accept.quality("identity"), 0);
+    assert_eq!(accept.negotiate(&["gzip", "identity"]), None);
+
+    let accept = AcceptEncoding::parse("").ok().unwrap();
+    assert_eq!(accept.negotiate(&["gzip", "identity"]), Some("identity"));
+}
+
+#[test]
+fn test_accept_from_headers() {
+    let mut headers = HeaderMap::new();
+    headers.append(request::ACCEPT, "text/html".parse().ok().unwrap());
+    headers.append(
+        request::ACCEPT,
+        "application/json;q=0.5".parse().ok().unwrap(),
+    );
+
+    let accept = Accept::from_headers(&headers).ok().unwrap();
+    assert_eq!(accept.ranges().len(), 2);
+
+    let language = AcceptLanguage::from_headers(&headers).ok().unwrap();
+    assert_eq!(language.quality("de"), 1000);
+}
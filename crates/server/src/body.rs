@@ -0,0 +1,473 @@
+use std::io::{BufRead, Read};
+
+use crate::header::{general, representation};
+use crate::media::Cursor;
+use crate::request::parse_field_line;
+use crate::{HeaderError, HeaderMap, RequestError};
+
+/// The longest chunk-size or trailer line read, extensions included.
+const MAX_CHUNK_LINE: usize = 4096;
+
+/// The most trailer fields read after the last chunk.
+const MAX_TRAILERS: usize = 100;
+
+/// How the body of a message is delimited, RFC 9112 §6.3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyFraming {
+    /// No body follows the head.
+    Empty,
+    /// Exactly this many bytes follow, from Content-Length.
+    Length(u64),
+    /// The body is sent with the chunked transfer coding.
+    Chunked,
+}
+
+impl BodyFraming {
+    /// Works out the framing of a request from its header fields.
+    ///
+    /// A request with both Transfer-Encoding and Content-Length, or with
+    /// more than one Content-Length, is rejected rather than guessed at,
+    /// since a proxy in front of us may have guessed differently. Chunked
+    /// must be the final coding and the only one, as no other transfer
+    /// coding is implemented.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, RequestError> {
+        let has_transfer_encoding = headers.contains_key(general::TRANSFER_ENCODING);
+        let has_content_length = headers.contains_key(representation::CONTENT_LENGTH);
+
+        match (has_transfer_encoding, has_content_length) {
+            (true, true) => Err(RequestError::ConflictingFraming),
+            (true, false) => Self::from_transfer_encoding(headers),
+            (false, true) => Self::from_content_length(headers),
+            (false, false) => Ok(BodyFraming::Empty),
+        }
+    }
+
+    /// Transfer-Encoding = #transfer-coding
+    fn from_transfer_encoding(headers: &HeaderMap) -> Result<Self, RequestError> {
+        let value = headers
+            .get_joined(general::TRANSFER_ENCODING)
+            .unwrap_or_default();
+        let codings: Vec<&[u8]> = value
+            .as_bytes()
+            .split(|b| *b == b',')
+            .map(|coding| coding.split(|b| *b == b';').next().unwrap_or_default())
+            .map(|coding| coding.trim_ascii())
+            .filter(|coding| !coding.is_empty())
+            .collect();
+
+        // RFC 9112 §6.3: without chunked last the length cannot be known,
+        // and chunked is never applied twice.
+        let is_chunked = |coding: &&[u8]| coding.eq_ignore_ascii_case(b"chunked");
+        match codings.split_last() {
+            Some((last, rest)) if is_chunked(last) && !rest.iter().any(is_chunked) => {
+                match rest.is_empty() {
+                    true => Ok(BodyFraming::Chunked),
+                    false => Err(RequestError::UnsupportedTransferCoding),
+                }
+            }
+            _ => Err(RequestError::InvalidTransferEncoding),
+        }
+    }
+
+    /// Content-Length = 1*DIGIT
+    fn from_content_length(headers: &HeaderMap) -> Result<Self, RequestError> {
+        let value = headers
+            .get_joined(representation::CONTENT_LENGTH)
+            .unwrap_or_default();
+        let length = value.as_bytes();
+
+        if length.contains(&b',') {
+            return Err(RequestError::DuplicateContentLength);
+        }
+        if length.is_empty() || !length.iter().all(u8::is_ascii_digit) {
+            return Err(RequestError::InvalidContentLength);
+        }
+        // Every byte is an ASCII digit, so only overflow can fail.
+        let length = std::str::from_utf8(length)
+            .ok()
+            .and_then(|length| length.parse::<u64>().ok())
+            .ok_or(RequestError::InvalidContentLength)?;
+
+        match length {
+            0 => Ok(BodyFraming::Empty),
+            length => Ok(BodyFraming::Length(length)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Bytes left of a Content-Length body.
+    Length(u64),
+    ChunkSize,
+    /// Bytes left of the current chunk.
+    ChunkData(u64),
+    /// The CRLF after a chunk's data.
+    ChunkEnd,
+    Trailers,
+    Done,
+}
+
+/// A request body read off the connection as it arrives.
+///
+/// `inner` is the connection positioned just after the head. The body
+/// never reads past its own end, so the bytes that follow are left in
+/// `inner` for the next pipelined request. Chunked bodies are decoded:
+/// chunk extensions are checked and dropped, and trailer fields are kept
+/// for [`Body::trailers`].
+///
+/// Reads fail with an [`std::io::Error`] that holds a [`RequestError`],
+/// found with [`RequestError::from_io`]. A body longer than the limit is
+/// [`RequestError::ContentTooLarge`], which is answered with 413.
+pub struct Body<R> {
+    inner: R,
+    state: State,
+    limit: u64,
+    /// Body bytes read or announced by a chunk-size so far.
+    length: u64,
+    trailers: HeaderMap,
+    line: Vec<u8>,
+}
+
+impl<R: BufRead> Body<R> {
+    /// Starts reading a body framed as `framing`, allowing at most `limit`
+    /// bytes. A Content-Length over the limit is rejected at once.
+    pub fn new(inner: R, framing: BodyFraming, limit: u64) -> Result<Self, RequestError> {
+        let state = match framing {
+            BodyFraming::Empty => State::Done,
+            BodyFraming::Length(length) if length > limit => {
+                return Err(RequestError::ContentTooLarge { limit })
+            }
+            BodyFraming::Length(length) => State::Length(length),
+            BodyFraming::Chunked => State::ChunkSize,
+        };
+        Ok(Self {
+            inner,
+            state,
+            limit,
+            length: 0,
+            trailers: HeaderMap::new(),
+            line: vec![],
+        })
+    }
+
+    /// The trailer fields of a chunked body, complete once the body has
+    /// been read to its end.
+    pub fn trailers(&self) -> &HeaderMap {
+        &self.trailers
+    }
+
+    /// Whether the whole body has been read.
+    pub fn is_done(&self) -> bool {
+        self.state == State::Done
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Copies up to `remaining` buffered bytes of data into `buf`.
+    fn read_data(&mut self, buf: &mut [u8], remaining: u64) -> std::io::Result<usize> {
+        let available = self.inner.fill_buf()?;
+        if available.is_empty() {
+            return Err(RequestError::Incomplete.into());
+        }
+        let count = buf
+            .len()
+            .min(available.len())
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        buf[..count].copy_from_slice(&available[..count]);
+        self.inner.consume(count);
+        Ok(count)
+    }
+
+    /// Reads the next line into `self.line`, without its terminator. Like
+    /// the head, lines may end in a bare LF.
+    fn read_line(&mut self) -> std::io::Result<()> {
+        self.line.clear();
+        loop {
+            let available = self.inner.fill_buf()?;
+            if available.is_empty() {
+                return Err(RequestError::Incomplete.into());
+            }
+            let (count, done) = match available.iter().position(|b| *b == b'\n') {
+                Some(idx) => (idx + 1, true),
+                None => (available.len(), false),
+            };
+            self.line.extend_from_slice(&available[..count]);
+            self.inner.consume(count);
+
+            if self.line.len() > MAX_CHUNK_LINE {
+                return Err(RequestError::InvalidChunk.into());
+            }
+            if done {
+                self.line.pop();
+                if self.line.last() == Some(&b'\r') {
+                    self.line.pop();
+                }
+                return Ok(());
+            }
+        }
+    }
+
+    /// Moves the decoder on by one step that is not data.
+    fn advance(&mut self) -> std::io::Result<()> {
+        self.state = match self.state {
+            State::Length(0) => State::Done,
+            State::ChunkData(0) => State::ChunkEnd,
+            State::ChunkSize => {
+                self.read_line()?;
+                let size = parse_chunk_line(&self.line)?;
+                if size > self.limit - self.length {
+                    return Err(RequestError::ContentTooLarge { limit: self.limit }.into());
+                }
+                self.length += size;
+                match size {
+                    0 => State::Trailers,
+                    size => State::ChunkData(size),
+                }
+            }
+            State::ChunkEnd => {
+                self.read_line()?;
+                if !self.line.is_empty() {
+                    return Err(RequestError::InvalidChunk.into());
+                }
+                State::ChunkSize
+            }
+            // trailer-section = *( field-line CRLF )
+            State::Trailers => {
+                self.read_line()?;
+                if self.line.is_empty() {
+                    State::Done
+                } else {
+                    if self.trailers.len() == MAX_TRAILERS {
+                        return Err(RequestError::TooManyHeaders {
+                            limit: MAX_TRAILERS,
+                        }
+                        .into());
+                    }
+                    let (name, value) = parse_field_line(&self.line, 0)?;
+                    self.trailers.append(name, value);
+                    State::Trailers
+                }
+            }
+            state => state,
+        };
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for Body<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            match self.state {
+                State::Done => return Ok(0),
+                State::Length(remaining) | State::ChunkData(remaining) if remaining > 0 => {
+                    if buf.is_empty() {
+                        return Ok(0);
+                    }
+                    let count = self.read_data(buf, remaining)?;
+                    let left = remaining - count as u64;
+                    self.state = match self.state {
+                        State::Length(_) => State::Length(left),
+                        _ => State::ChunkData(left),
+                    };
+                    return Ok(count);
+                }
+                _ => self.advance()?,
+            }
+        }
+    }
+}
+
+/// chunk = chunk-size [ chunk-ext ] CRLF
+///
+/// chunk-ext = *( BWS ";" BWS chunk-ext-name [ BWS "=" BWS chunk-ext-val ] )
+///
+/// Returns the size; extensions are checked and ignored, as RFC 9112 §7.1.1
+/// asks of recipients that do not know them.
+fn parse_chunk_line(line: &[u8]) -> Result<u64, RequestError> {
+    let digits = line.iter().take_while(|b| b.is_ascii_hexdigit()).count();
+    // Sixteen hex digits already fill a u64.
+    if digits == 0 || digits > 16 {
+        return Err(RequestError::InvalidChunk);
+    }
+    let size = line[..digits].iter().fold(0, |size, b| {
+        // Only hex digits were counted above.
+        size * 16 + u64::from((*b as char).to_digit(16).unwrap_or_default())
+    });
+
+    let extensions =
+        std::str::from_utf8(&line[digits..]).map_err(|_| RequestError::InvalidChunk)?;
+    check_chunk_extensions(&mut Cursor::new(extensions)).map_err(|_| RequestError::InvalidChunk)?;
+
+    Ok(size)
+}
+
+fn check_chunk_extensions(cursor: &mut Cursor<'_>) -> Result<(), HeaderError> {
+    loop {
+        cursor.skip_ows();
+        if cursor.is_end() {
+            return Ok(());
+        }
+        cursor.expect(b';')?;
+        cursor.skip_ows();
+        cursor.token()?;
+        cursor.skip_ows();
+        if cursor.eat(b'=') {
+            cursor.skip_ows();
+            cursor.param_value()?;
+        }
+    }
+}
+
+#[cfg(test)]
+fn headers(fields: &[(&str, &str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in fields {
+        headers.append(name.parse().ok().unwrap(), value.parse().ok().unwrap());
+    }
+    headers
+}
+
+#[cfg(test)]
+fn read_body(input: &[u8], framing: BodyFraming, limit: u64) -> std::io::Result<Vec<u8>> {
+    let mut body = Body::new(input, framing, limit).map_err(std::io::Error::from)?;
+    let mut out = vec![];
+    body.read_to_end(&mut out)?;
+    Ok(out)
+}
+
+#[test]
+fn test_framing_from_headers() {
+    let framing = |fields: &[(&str, &str)]| BodyFraming::from_headers(&headers(fields));
+
+    assert_eq!(framing(&[]), Ok(BodyFraming::Empty));
+    assert_eq!(
+        framing(&[("Content-Length", "42")]),
+        Ok(BodyFraming::Length(42))
+    );
+    assert_eq!(
+        framing(&[("Transfer-Encoding", "Chunked")]),
+        Ok(BodyFraming::Chunked)
+    );
+    assert_eq!(
+        framing(&[("Transfer-Encoding", "chunked"), ("Content-Length", "5")]),
+        Err(RequestError::ConflictingFraming)
+    );
+    assert_eq!(
+        framing(&[("Content-Length", "5"), ("Content-Length", "5")]),
+        Err(RequestError::DuplicateContentLength)
+    );
+    assert_eq!(
+        framing(&[("Content-Length", "5, 6")]),
+        Err(RequestError::DuplicateContentLength)
+    );
+    assert_eq!(
+        framing(&[("Content-Length", "+5")]),
+        Err(RequestError::InvalidContentLength)
+    );
+    assert_eq!(
+        framing(&[("Content-Length", "99999999999999999999")]),
+        Err(RequestError::InvalidContentLength)
+    );
+    assert_eq!(
+        framing(&[("Transfer-Encoding", "chunked, gzip")]),
+        Err(RequestError::InvalidTransferEncoding)
+    );
+    assert_eq!(
+        framing(&[
+            ("Transfer-Encoding", "chunked"),
+            ("Transfer-Encoding", "chunked")
+        ]),
+        Err(RequestError::InvalidTransferEncoding)
+    );
+    assert_eq!(
+        framing(&[("Transfer-Encoding", "gzip, chunked")]),
+        Err(RequestError::UnsupportedTransferCoding)
+    );
+}
+
+#[test]
+fn test_content_length_body() {
+    let mut input = &b"hello worldGET /next HTTP/1.1"[..];
+
+    let mut body = Body::new(&mut input, BodyFraming::Length(11), 64)
+        .ok()
+        .unwrap();
+    let mut out = String::new();
+    body.read_to_string(&mut out).ok().unwrap();
+    assert_eq!(out, "hello world");
+    assert!(body.is_done());
+    // The next request is left unread.
+    assert_eq!(input, b"GET /next HTTP/1.1");
+
+    let error = read_body(b"short", BodyFraming::Length(11), 64)
+        .err()
+        .unwrap();
+    assert_eq!(
+        RequestError::from_io(&error),
+        Some(&RequestError::Incomplete)
+    );
+}
+
+#[test]
+fn test_chunked_body() {
+    // RFC 9112 §7.1 with an extension and a trailer field.
+    let mut input = &b"4\r\nWiki\r\n6;name=\"a b\"\r\npedia \r\nE\r\nin \r\n\r\nchunks.\r\n0\r\nExpires: never\r\n\r\nnext"[..];
+
+    let mut body = Body::new(&mut input, BodyFraming::Chunked, 64)
+        .ok()
+        .unwrap();
+    let mut out = String::new();
+    body.read_to_string(&mut out).ok().unwrap();
+    assert_eq!(out, "Wikipedia in \r\n\r\nchunks.");
+    assert_eq!(body.trailers().get("expires").unwrap(), "never");
+    assert_eq!(input, b"next");
+}
+
+#[test]
+fn test_chunked_errors() {
+    let error = |input: &[u8]| {
+        let error = read_body(input, BodyFraming::Chunked, 64).err().unwrap();
+        RequestError::from_io(&error).cloned()
+    };
+
+    assert_eq!(error(b"x\r\n"), Some(RequestError::InvalidChunk));
+    assert_eq!(error(b"3\r\nabcX\r\n"), Some(RequestError::InvalidChunk));
+    assert_eq!(
+        error(b"3;=x\r\nabc\r\n0\r\n\r\n"),
+        Some(RequestError::InvalidChunk)
+    );
+    assert_eq!(
+        error(b"ffffffffffffffffff\r\n"),
+        Some(RequestError::InvalidChunk)
+    );
+    assert_eq!(error(b"3\r\nab"), Some(RequestError::Incomplete));
+    assert_eq!(
+        error(b"3\r\nabc\r\n0\r\nBad Name: x\r\n\r\n"),
+        Some(RequestError::InvalidHeaderName {
+            offset: 3,
+            found: ' '
+        })
+    );
+}
+
+#[test]
+fn test_body_limit() {
+    assert_eq!(
+        Body::new(&b""[..], BodyFraming::Length(65), 64).err(),
+        Some(RequestError::ContentTooLarge { limit: 64 })
+    );
+
+    let input = b"20\r\n0123456789abcdef0123456789abcdef\r\n21\r\n";
+    let error = read_body(input, BodyFraming::Chunked, 64).err().unwrap();
+    assert_eq!(
+        RequestError::from_io(&error),
+        Some(&RequestError::ContentTooLarge { limit: 64 })
+    );
+    assert_eq!(
+        crate::StatusCode::from(RequestError::from_io(&error).unwrap()),
+        crate::StatusCode::ContentTooLarge
+    );
+}
@@ -0,0 +1,255 @@
+use crate::{
+    is_fragment_char, is_password_char, is_pchar, is_user_char, percent_encode, Host,
+    HttpSchemeEnum, Query, Tokenizer, Uri, UriError,
+};
+
+/// Builds a [`Uri`] from unescaped components.
+///
+/// Each component is percent-encoded for its position, so path segments may
+/// contain '/' and query values may contain '&'. The assembled string is run
+/// through the same parser as any other URI, so `build` never returns a
+/// `Uri` that could not have been parsed.
+///
+/// ```
+/// use server::{HttpSchemeEnum, UriBuilder};
+///
+/// let uri = UriBuilder::new()
+///     .scheme(HttpSchemeEnum::HTTPS)
+///     .host("telemakos.io")
+///     .path_segment("docs")
+///     .path_segment("a/b")
+///     .query_pair("q", "rust http")
+///     .build()
+///     .unwrap();
+/// assert_eq!(uri.to_string(), "https://telemakos.io/docs/a%2Fb?q=rust+http");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UriBuilder {
+    scheme: Option<HttpSchemeEnum>,
+    username: Option<String>,
+    password: Option<String>,
+    host: Option<String>,
+    port: Option<u16>,
+    segments: Vec<String>,
+    query: Option<Query>,
+    fragment: Option<String>,
+}
+
+impl UriBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn scheme(mut self, scheme: HttpSchemeEnum) -> Self {
+        self.scheme = Some(scheme);
+        self
+    }
+
+    pub fn userinfo(mut self, username: &str, password: Option<&str>) -> Self {
+        self.username = Some(String::from(username));
+        self.password = password.map(String::from);
+        self
+    }
+
+    /// A registered name, an IPv4 address, or an IPv6 address with or
+    /// without brackets. `build` rejects any other host, such as one that
+    /// holds a '@' or '/'.
+    pub fn host(mut self, host: &str) -> Self {
+        self.host = Some(String::from(host));
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Appends one unescaped path segment. An empty segment produces a
+    /// trailing or doubled '/'.
+    pub fn path_segment(mut self, segment: &str) -> Self {
+        self.segments.push(String::from(segment));
+        self
+    }
+
+    pub fn path_segments<'a, I: IntoIterator<Item = &'a str>>(mut self, segments: I) -> Self {
+        self.segments.extend(segments.into_iter().map(String::from));
+        self
+    }
+
+    /// Appends an unescaped key/value pair to the query.
+    pub fn query_pair(mut self, key: &str, value: &str) -> Self {
+        self.query.get_or_insert_with(Query::new).append(key, value);
+        self
+    }
+
+    pub fn query(mut self, query: Query) -> Self {
+        self.query = Some(query);
+        self
+    }
+
+    pub fn fragment(mut self, fragment: &str) -> Self {
+        self.fragment = Some(String::from(fragment));
+        self
+    }
+
+    /// Assembles and parses the URI. A scheme is required, and so is a host
+    /// for schemes that need one.
+    pub fn build(self) -> Result<Uri, UriError> {
+        let scheme = match self.scheme {
+            Some(HttpSchemeEnum::Unknown) | None => return Err(UriError::NotAbsolute),
+            Some(scheme) => scheme,
+        };
+        if self.host.is_none() && scheme.requires_host() {
+            return Err(UriError::EmptyHost);
+        }
+
+        let mut out = format!("{}://", scheme.as_str());
+        if let Some(username) = &self.username {
+            out.push_str(&percent_encode(username, is_user_char));
+            if let Some(password) = &self.password {
+                out.push(':');
+                out.push_str(&percent_encode(password, is_password_char));
+            }
+            out.push('@');
+        }
+        // The host is checked on its own, so an '@', '/', '?' or '#' in it
+        // cannot start another component. Offsets are into the assembled
+        // URI, like those of every other error from here.
+        if let Some(host) = &self.host {
+            let host = if host.contains(':') && !host.starts_with('[') {
+                // A bare IPv6 address, which only needs its brackets.
+                match host.parse::<std::net::Ipv6Addr>() {
+                    Ok(addr) => Host::Ipv6 {
+                        addr,
+                        zone_id: None,
+                    },
+                    Err(_) => return Err(UriError::InvalidHost { offset: out.len() }),
+                }
+            } else {
+                Host::parse(host).map_err(|e| e.offset_by(out.len()))?
+            };
+            out.push_str(&host.to_string());
+        }
+        if let Some(port) = self.port {
+            out.push_str(&format!(":{}", port));
+        }
+        for segment in &self.segments {
+            out.push('/');
+            out.push_str(&percent_encode(segment, is_pchar));
+        }
+        if let Some(query) = &self.query {
+            out.push('?');
+            out.push_str(&query.to_string());
+        }
+        if let Some(fragment) = &self.fragment {
+            out.push('#');
+            out.push_str(&percent_encode(fragment, is_fragment_char));
+        }
+
+        let mut tokenizer = Tokenizer::new(out);
+        Uri::parse_tokens(&mut tokenizer)
+    }
+}
+
+#[test]
+fn test_builder_all_components() {
+    let uri = UriBuilder::new()
+        .scheme(HttpSchemeEnum::HTTP)
+        .userinfo("proxy user", Some("p@ss:word"))
+        .host("telemakos.io")
+        .port(3128)
+        .path_segments(["docs", "100% done", ""])
+        .query_pair("q", "a&b")
+        .query_pair("q", "c")
+        .fragment("sec 1")
+        .build()
+        .ok()
+        .unwrap();
+
+    assert_eq!(
+        uri.to_string(),
+        "http://proxy%20user:p%40ss:word@telemakos.io:3128/docs/100%25%20done/?q=a%26b&q=c#sec%201"
+    );
+    assert_eq!(uri.username(), Some("proxy user"));
+    assert_eq!(uri.password(), Some("p@ss:word"));
+    assert_eq!(
+        uri.path_segments().collect::<Vec<_>>(),
+        vec!["docs", "100% done", ""]
+    );
+    assert_eq!(
+        uri.query_pairs().ok().unwrap().get_all("q"),
+        vec!["a&b", "c"]
+    );
+}
+
+#[test]
+fn test_builder_ipv6_host() {
+    let uri = UriBuilder::new()
+        .scheme(HttpSchemeEnum::HTTP)
+        .host("::1")
+        .port(8080)
+        .build()
+        .ok()
+        .unwrap();
+
+    assert_eq!(uri.to_string(), "http://[::1]:8080");
+    assert_eq!(uri.port(), Some(8080));
+}
+
+#[test]
+fn test_builder_requires_scheme_and_host() {
+    assert_eq!(
+        UriBuilder::new().host("telemakos.io").build(),
+        Err(UriError::NotAbsolute)
+    );
+    assert_eq!(
+        UriBuilder::new().scheme(HttpSchemeEnum::HTTPS).build(),
+        Err(UriError::EmptyHost)
+    );
+    assert_eq!(
+        UriBuilder::new()
+            .scheme(HttpSchemeEnum::HTTPS)
+            .host("tele makos.io")
+            .build(),
+        Err(UriError::InvalidHostChar {
+            offset: 12,
+            found: ' '
+        })
+    );
+}
+
+#[test]
+fn test_builder_rejects_host_delimiters() {
+    let build = |host| {
+        UriBuilder::new()
+            .scheme(HttpSchemeEnum::HTTPS)
+            .host(host)
+            .build()
+    };
+
+    assert_eq!(
+        build("evil.com@victim.com"),
+        Err(UriError::InvalidHostChar {
+            offset: 16,
+            found: '@'
+        })
+    );
+    assert_eq!(
+        build("victim.com/admin?x="),
+        Err(UriError::InvalidHostChar {
+            offset: 18,
+            found: '/'
+        })
+    );
+    assert_eq!(
+        build("victim.com:80"),
+        Err(UriError::InvalidHost { offset: 8 })
+    );
+    assert_eq!(
+        build("victim.com#x"),
+        Err(UriError::InvalidHostChar {
+            offset: 18,
+            found: '#'
+        })
+    );
+}
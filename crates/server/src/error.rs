@@ -0,0 +1,396 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::Tag;
+
+/// Why a URI could not be parsed or built.
+///
+/// Offsets are byte offsets into the string that was being parsed, and
+/// `found` is the character at that offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UriError {
+    /// A character that cannot appear in a scheme.
+    InvalidScheme { offset: usize, found: char },
+    /// A character that cannot appear in a registered name.
+    InvalidHostChar { offset: usize, found: char },
+    /// A malformed IPv6 or IPvFuture literal, or zone ID.
+    InvalidHost { offset: usize },
+    /// An internationalized host label that has no valid ASCII form.
+    InvalidIdn { offset: usize },
+    /// An http or https URI without a host.
+    EmptyHost,
+    /// A port number above 65535.
+    PortOverflow { offset: usize },
+    /// A `%` that is not followed by two hex digits, or escapes that do not
+    /// decode to UTF-8.
+    InvalidPercentEscape { offset: usize },
+    /// A character that cannot appear in the given component.
+    InvalidChar {
+        offset: usize,
+        found: char,
+        component: Tag,
+    },
+    /// The input is longer than the configured limit.
+    UriTooLong { length: usize, limit: usize },
+    /// The input ended in the middle of a component.
+    UnexpectedEnd { offset: usize },
+    /// A relative reference where an absolute URI is required.
+    NotAbsolute,
+    /// A URI with components beyond scheme, host and port where an origin
+    /// is required.
+    NotAnOrigin,
+}
+
+impl UriError {
+    /// The byte offset the error was found at, when it has one.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            UriError::InvalidScheme { offset, .. }
+            | UriError::InvalidHostChar { offset, .. }
+            | UriError::InvalidHost { offset }
+            | UriError::InvalidIdn { offset }
+            | UriError::PortOverflow { offset }
+            | UriError::InvalidPercentEscape { offset }
+            | UriError::InvalidChar { offset, .. }
+            | UriError::UnexpectedEnd { offset } => Some(*offset),
+            UriError::EmptyHost
+            | UriError::UriTooLong { .. }
+            | UriError::NotAbsolute
+            | UriError::NotAnOrigin => None,
+        }
+    }
+
+    /// Moves the offset from the start of a component to the start of the
+    /// whole input.
+    pub(crate) fn offset_by(mut self, base: usize) -> Self {
+        match &mut self {
+            UriError::InvalidScheme { offset, .. }
+            | UriError::InvalidHostChar { offset, .. }
+            | UriError::InvalidHost { offset }
+            | UriError::InvalidIdn { offset }
+            | UriError::PortOverflow { offset }
+            | UriError::InvalidPercentEscape { offset }
+            | UriError::InvalidChar { offset, .. }
+            | UriError::UnexpectedEnd { offset } => *offset += base,
+            _ => {}
+        }
+        self
+    }
+}
+
+impl Display for UriError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UriError::InvalidScheme { offset, found } => {
+                write!(f, "invalid scheme character {:?} at {}", found, offset)
+            }
+            UriError::InvalidHostChar { offset, found } => {
+                write!(f, "invalid host character {:?} at {}", found, offset)
+            }
+            UriError::InvalidHost { offset } => write!(f, "invalid IP literal at {}", offset),
+            UriError::InvalidIdn { offset } => {
+                write!(f, "invalid internationalized host label at {}", offset)
+            }
+            UriError::EmptyHost => write!(f, "empty host"),
+            UriError::PortOverflow { offset } => {
+                write!(f, "port at {} is larger than 65535", offset)
+            }
+            UriError::InvalidPercentEscape { offset } => {
+                write!(f, "invalid percent-escape at {}", offset)
+            }
+            UriError::InvalidChar {
+                offset,
+                found,
+                component,
+            } => write!(
+                f,
+                "invalid {:?} character {:?} at {}",
+                component, found, offset
+            ),
+            UriError::UriTooLong { length, limit } => {
+                write!(f, "URI of {} bytes exceeds the limit of {}", length, limit)
+            }
+            UriError::UnexpectedEnd { offset } => write!(f, "unexpected end of URI at {}", offset),
+            UriError::NotAbsolute => write!(f, "relative reference where a URI is required"),
+            UriError::NotAnOrigin => write!(f, "URI has components beyond an origin"),
+        }
+    }
+}
+
+impl Error for UriError {}
+
+/// Why a URI template could not be parsed or expanded into a [`Uri`].
+///
+/// Offsets are byte offsets into the template.
+///
+/// [`Uri`]: crate::Uri
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// A '{' without a matching '}'.
+    UnclosedExpression { offset: usize },
+    /// An operator reserved for future extensions, like `=` or `!`.
+    InvalidOperator { offset: usize, found: char },
+    /// A character that cannot appear in a variable name.
+    InvalidVarname { offset: usize, found: char },
+    /// A prefix modifier that is not a number from 1 to 9999.
+    InvalidPrefix { offset: usize },
+    /// A character that cannot appear outside an expression.
+    InvalidLiteral { offset: usize, found: char },
+    /// The expansion is not a valid URI.
+    Uri(UriError),
+}
+
+impl From<UriError> for TemplateError {
+    fn from(error: UriError) -> Self {
+        TemplateError::Uri(error)
+    }
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TemplateError::UnclosedExpression { offset } => {
+                write!(f, "unclosed expression at {}", offset)
+            }
+            TemplateError::InvalidOperator { offset, found } => {
+                write!(f, "reserved operator {:?} at {}", found, offset)
+            }
+            TemplateError::InvalidVarname { offset, found } => {
+                write!(
+                    f,
+                    "invalid variable name character {:?} at {}",
+                    found, offset
+                )
+            }
+            TemplateError::InvalidPrefix { offset } => {
+                write!(f, "invalid prefix length at {}", offset)
+            }
+            TemplateError::InvalidLiteral { offset, found } => {
+                write!(f, "invalid literal character {:?} at {}", found, offset)
+            }
+            TemplateError::Uri(e) => write!(f, "expanded template is not a URI: {}", e),
+        }
+    }
+}
+
+impl Error for TemplateError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TemplateError::Uri(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Why an HTTP request could not be parsed.
+///
+/// Offsets are byte offsets into the bytes that were being parsed, and
+/// `found` is the character at that offset. The end of a line is reported
+/// as '\n'.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestError {
+    /// A method that is empty or not a token.
+    InvalidMethod { offset: usize, found: char },
+    /// A request-line without exactly one SP between its three parts.
+    InvalidRequestLine { offset: usize, found: char },
+    /// A request-target that is not a valid origin, absolute, authority or
+    /// asterisk form. Offsets are shifted to the start of the request.
+    InvalidTarget(UriError),
+    /// An HTTP-version that is not `HTTP/DIGIT.DIGIT`.
+    InvalidVersion { offset: usize },
+    /// A well-formed HTTP-version with a major version other than 1.
+    UnsupportedVersion { major: u8, minor: u8 },
+    /// An HTTP/1.0 request with a Transfer-Encoding field.
+    TransferEncodingInHttp10,
+    /// A field name that is empty, not a token or followed by whitespace.
+    InvalidHeaderName { offset: usize, found: char },
+    /// A control character inside a field value.
+    InvalidHeaderValue { offset: usize, found: char },
+    /// A field value folded onto the next line, when folding is rejected.
+    ObsFold { offset: usize },
+    /// An HTTP/1.1 request without a Host field.
+    MissingHost,
+    /// A request with more than one Host field.
+    DuplicateHost,
+    /// A Host field that is not `uri-host [ ":" port ]`.
+    InvalidHostHeader,
+    /// More field lines than the configured limit.
+    TooManyHeaders { limit: usize },
+    /// A request head longer than the configured limit, in bytes.
+    HeadTooLarge { limit: usize },
+    /// The input ended before the empty line that closes the head, or
+    /// before the end of the body.
+    Incomplete,
+    /// A request with both Transfer-Encoding and Content-Length.
+    ConflictingFraming,
+    /// More than one Content-Length value, even if they agree.
+    DuplicateContentLength,
+    /// A Content-Length that is not a decimal number that fits a u64.
+    InvalidContentLength,
+    /// A Transfer-Encoding whose final coding is not chunked, or that
+    /// applies chunked more than once.
+    InvalidTransferEncoding,
+    /// A transfer coding other than chunked, which is not implemented.
+    UnsupportedTransferCoding,
+    /// A malformed chunk-size line or chunk terminator.
+    InvalidChunk,
+    /// A body longer than the configured limit, in bytes.
+    ContentTooLarge { limit: u64 },
+}
+
+impl RequestError {
+    /// The byte offset the error was found at, when it has one.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            RequestError::InvalidMethod { offset, .. }
+            | RequestError::InvalidRequestLine { offset, .. }
+            | RequestError::InvalidVersion { offset }
+            | RequestError::InvalidHeaderName { offset, .. }
+            | RequestError::InvalidHeaderValue { offset, .. }
+            | RequestError::ObsFold { offset } => Some(*offset),
+            RequestError::InvalidTarget(e) => e.offset(),
+            _ => None,
+        }
+    }
+
+    /// The request error inside an error from reading a
+    /// [`Body`](crate::Body).
+    pub fn from_io(error: &std::io::Error) -> Option<&RequestError> {
+        error.get_ref()?.downcast_ref()
+    }
+
+    /// Moves the offset of an error found in a slice that starts `base`
+    /// bytes into the request.
+    pub(crate) fn offset_by(mut self, base: usize) -> Self {
+        match &mut self {
+            RequestError::InvalidMethod { offset, .. }
+            | RequestError::InvalidRequestLine { offset, .. }
+            | RequestError::InvalidVersion { offset }
+            | RequestError::InvalidHeaderName { offset, .. }
+            | RequestError::InvalidHeaderValue { offset, .. }
+            | RequestError::ObsFold { offset } => *offset += base,
+            RequestError::InvalidTarget(e) => *e = e.clone().offset_by(base),
+            _ => {}
+        }
+        self
+    }
+}
+
+impl Display for RequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestError::InvalidMethod { offset, found } => {
+                write!(f, "invalid method character {:?} at {}", found, offset)
+            }
+            RequestError::InvalidRequestLine { offset, found } => {
+                write!(f, "malformed request-line, found {:?} at {}", found, offset)
+            }
+            RequestError::InvalidTarget(e) => write!(f, "invalid request-target: {}", e),
+            RequestError::InvalidVersion { offset } => {
+                write!(f, "invalid HTTP-version at {}", offset)
+            }
+            RequestError::InvalidHeaderName { offset, found } => {
+                write!(f, "invalid field name character {:?} at {}", found, offset)
+            }
+            RequestError::InvalidHeaderValue { offset, found } => {
+                write!(f, "invalid field value character {:?} at {}", found, offset)
+            }
+            RequestError::UnsupportedVersion { major, minor } => {
+                write!(f, "unsupported version HTTP/{}.{}", major, minor)
+            }
+            RequestError::TransferEncodingInHttp10 => {
+                write!(f, "Transfer-Encoding in an HTTP/1.0 request")
+            }
+            RequestError::ObsFold { offset } => {
+                write!(f, "obsolete line folding at {}", offset)
+            }
+            RequestError::MissingHost => write!(f, "HTTP/1.1 request without a Host field"),
+            RequestError::DuplicateHost => write!(f, "more than one Host field"),
+            RequestError::InvalidHostHeader => write!(f, "invalid Host field value"),
+            RequestError::TooManyHeaders { limit } => {
+                write!(f, "more than {} header fields", limit)
+            }
+            RequestError::HeadTooLarge { limit } => {
+                write!(f, "request head longer than {} bytes", limit)
+            }
+            RequestError::Incomplete => write!(f, "request is incomplete"),
+            RequestError::ConflictingFraming => {
+                write!(f, "both Transfer-Encoding and Content-Length")
+            }
+            RequestError::DuplicateContentLength => write!(f, "more than one Content-Length"),
+            RequestError::InvalidContentLength => write!(f, "invalid Content-Length"),
+            RequestError::InvalidTransferEncoding => {
+                write!(f, "Transfer-Encoding does not end in a single chunked")
+            }
+            RequestError::UnsupportedTransferCoding => {
+                write!(f, "unsupported transfer coding")
+            }
+            RequestError::InvalidChunk => write!(f, "malformed chunk"),
+            RequestError::ContentTooLarge { limit } => {
+                write!(f, "body longer than {} bytes", limit)
+            }
+        }
+    }
+}
+
+/// Lets a [`Body`](crate::Body) read fail with the reason inside. A body
+/// that ends early is an unexpected EOF.
+impl From<RequestError> for std::io::Error {
+    fn from(error: RequestError) -> Self {
+        let kind = match error {
+            RequestError::Incomplete => std::io::ErrorKind::UnexpectedEof,
+            _ => std::io::ErrorKind::InvalidData,
+        };
+        std::io::Error::new(kind, error)
+    }
+}
+
+impl Error for RequestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RequestError::InvalidTarget(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Why a typed header field value, such as a media type or an Accept list,
+/// could not be parsed.
+///
+/// Offsets are byte offsets into the field value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderError {
+    /// A character that does not fit the field's grammar.
+    InvalidChar { offset: usize, found: char },
+    /// A q-value above 1 or with more than three decimals.
+    InvalidQuality { offset: usize },
+    /// The value ended where more was expected, as in `text` without a
+    /// subtype or an unclosed quoted-string.
+    UnexpectedEnd { offset: usize },
+}
+
+impl HeaderError {
+    pub fn offset(&self) -> usize {
+        match self {
+            HeaderError::InvalidChar { offset, .. }
+            | HeaderError::InvalidQuality { offset }
+            | HeaderError::UnexpectedEnd { offset } => *offset,
+        }
+    }
+}
+
+impl Display for HeaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeaderError::InvalidChar { offset, found } => {
+                write!(f, "invalid character {:?} at {}", found, offset)
+            }
+            HeaderError::InvalidQuality { offset } => write!(f, "invalid q-value at {}", offset),
+            HeaderError::UnexpectedEnd { offset } => {
+                write!(f, "unexpected end of field value at {}", offset)
+            }
+        }
+    }
+}
+
+impl Error for HeaderError {}
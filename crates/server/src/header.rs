@@ -0,0 +1,443 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::{char_at, is_tchar, RequestError};
+
+/// A field name. Names are case-insensitive, so they are stored lowercase.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HeaderName(Cow<'static, str>);
+
+impl HeaderName {
+    /// field-name = token
+    pub fn parse(bytes: &[u8]) -> Result<Self, RequestError> {
+        if bytes.is_empty() {
+            return Err(RequestError::InvalidHeaderName {
+                offset: 0,
+                found: '\0',
+            });
+        }
+        if let Some(offset) = bytes.iter().position(|b| !is_tchar(*b)) {
+            return Err(RequestError::InvalidHeaderName {
+                offset,
+                found: char_at(bytes, offset),
+            });
+        }
+        // tchar is ASCII, so the token is valid UTF-8.
+        let name = String::from_utf8_lossy(bytes).to_ascii_lowercase();
+        Ok(HeaderName(Cow::Owned(name)))
+    }
+
+    /// The name, lowercase.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for HeaderName {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl FromStr for HeaderName {
+    type Err = RequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HeaderName::parse(s.as_bytes())
+    }
+}
+
+impl Display for HeaderName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A field value, kept as bytes since obs-text is not UTF-8.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct HeaderValue(Vec<u8>);
+
+impl HeaderValue {
+    /// field-value = *field-content, made of field-vchar, SP and HTAB.
+    ///
+    /// field-vchar = VCHAR / obs-text
+    ///
+    /// Surrounding whitespace is not part of a value and should already be
+    /// trimmed.
+    pub fn parse(bytes: &[u8]) -> Result<Self, RequestError> {
+        match bytes
+            .iter()
+            .position(|b| !matches!(b, b'\t' | b' '..=b'~' | 0x80..=0xFF))
+        {
+            Some(offset) => Err(RequestError::InvalidHeaderValue {
+                offset,
+                found: char_at(bytes, offset),
+            }),
+            None => Ok(HeaderValue(bytes.to_vec())),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The value as text, if it has no obs-text.
+    pub fn to_str(&self) -> Option<&str> {
+        match self.0.is_ascii() {
+            true => std::str::from_utf8(&self.0).ok(),
+            false => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Appends a folded continuation line, replacing the fold with a
+    /// single space, RFC 9112 §5.2.
+    pub(crate) fn push_fold(&mut self, continuation: &[u8]) {
+        self.0.push(b' ');
+        self.0.extend_from_slice(continuation);
+    }
+}
+
+impl FromStr for HeaderValue {
+    type Err = RequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HeaderValue::parse(s.as_bytes())
+    }
+}
+
+/// Decimal numbers, as in Content-Length, are always a valid value.
+impl From<u64> for HeaderValue {
+    fn from(number: u64) -> Self {
+        HeaderValue(number.to_string().into_bytes())
+    }
+}
+
+impl PartialEq<str> for HeaderValue {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for HeaderValue {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl PartialEq<[u8]> for HeaderValue {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.0 == other
+    }
+}
+
+/// Writes the value with obs-text replaced, for logs.
+impl Display for HeaderValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.0))
+    }
+}
+
+/// The header fields of a message, in the order they were received or
+/// added. A name may appear on more than one field line.
+///
+/// Lookups take anything that reads as a name, so `HOST`, `&HOST` and
+/// `"Host"` all work, and compare case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderMap {
+    entries: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl HeaderMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of field lines, counting repeated names once per line.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, name: impl AsRef<str>) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// The value of the first field with this name.
+    pub fn get(&self, name: impl AsRef<str>) -> Option<&HeaderValue> {
+        self.get_all(name).next()
+    }
+
+    /// The values of every field with this name, in order.
+    pub fn get_all(&self, name: impl AsRef<str>) -> impl Iterator<Item = &HeaderValue> {
+        self.entries
+            .iter()
+            .filter(move |(n, _)| n.as_str().eq_ignore_ascii_case(name.as_ref()))
+            .map(|(_, v)| v)
+    }
+
+    /// Every value of a list-based field combined into one, separated by
+    /// ", ", as RFC 9110 §5.3 allows. Not meant for Set-Cookie, whose
+    /// values may themselves hold commas.
+    pub fn get_joined(&self, name: impl AsRef<str>) -> Option<HeaderValue> {
+        let mut values = self.get_all(name);
+        let mut joined = values.next()?.clone();
+        for value in values {
+            joined.0.extend_from_slice(b", ");
+            joined.0.extend_from_slice(&value.0);
+        }
+        Some(joined)
+    }
+
+    /// Adds a field line after the others, keeping any with the same name.
+    pub fn append(&mut self, name: HeaderName, value: HeaderValue) {
+        self.entries.push((name, value));
+    }
+
+    /// Sets the only value of a field. The new line takes the place of the
+    /// first existing one and any others are dropped. Returns the first
+    /// value that was replaced.
+    pub fn insert(&mut self, name: HeaderName, value: HeaderValue) -> Option<HeaderValue> {
+        match self.entries.iter().position(|(n, _)| *n == name) {
+            Some(idx) => {
+                let old = std::mem::replace(&mut self.entries[idx].1, value);
+                let mut index = 0;
+                self.entries.retain(|(n, _)| {
+                    let keep = index <= idx || *n != name;
+                    index += 1;
+                    keep
+                });
+                Some(old)
+            }
+            None => {
+                self.entries.push((name, value));
+                None
+            }
+        }
+    }
+
+    /// Removes every field with this name, returning the first value.
+    pub fn remove(&mut self, name: impl AsRef<str>) -> Option<HeaderValue> {
+        let name = name.as_ref();
+        let mut removed = None;
+        self.entries.retain(|(n, v)| {
+            if !n.as_str().eq_ignore_ascii_case(name) {
+                return true;
+            }
+            removed.get_or_insert_with(|| v.clone());
+            false
+        });
+        removed
+    }
+
+    /// Every field line in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&HeaderName, &HeaderValue)> {
+        self.entries.iter().map(|(n, v)| (n, v))
+    }
+
+    /// Writes every field line, each ended by CRLF, with values as the
+    /// raw bytes they were received or built from.
+    pub fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        for (name, value) in self.iter() {
+            out.write_all(name.as_str().as_bytes())?;
+            out.write_all(b": ")?;
+            out.write_all(value.as_bytes())?;
+            out.write_all(b"\r\n")?;
+        }
+        Ok(())
+    }
+
+    /// The value of the last field line, for obs-fold continuations.
+    pub(crate) fn last_value_mut(&mut self) -> Option<&mut HeaderValue> {
+        self.entries.last_mut().map(|(_, v)| v)
+    }
+}
+
+/// Writes every field line, each ended by CRLF, with obs-text replaced.
+impl Display for HeaderMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, value) in self.iter() {
+            write!(f, "{}: {}\r\n", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! header_names {
+    ($($(#[$doc:meta])* $name:ident => $value:literal,)*) => {
+        $(
+            $(#[$doc])*
+            pub const $name: HeaderName = HeaderName(Cow::Borrowed($value));
+        )*
+    };
+}
+
+/// Fields a client sends about the request and itself, RFC 9110 §10.1
+/// and §12.5.
+pub mod request {
+    use super::*;
+
+    header_names! {
+        ACCEPT => "accept",
+        ACCEPT_CHARSET => "accept-charset",
+        ACCEPT_ENCODING => "accept-encoding",
+        ACCEPT_LANGUAGE => "accept-language",
+        AUTHORIZATION => "authorization",
+        COOKIE => "cookie",
+        EXPECT => "expect",
+        FROM => "from",
+        HOST => "host",
+        IF_MATCH => "if-match",
+        IF_MODIFIED_SINCE => "if-modified-since",
+        IF_NONE_MATCH => "if-none-match",
+        IF_RANGE => "if-range",
+        IF_UNMODIFIED_SINCE => "if-unmodified-since",
+        MAX_FORWARDS => "max-forwards",
+        RANGE => "range",
+        REFERER => "referer",
+        TE => "te",
+        USER_AGENT => "user-agent",
+    }
+}
+
+/// Fields about the connection or the message as a whole, sent in both
+/// directions.
+pub mod general {
+    use super::*;
+
+    header_names! {
+        CACHE_CONTROL => "cache-control",
+        CONNECTION => "connection",
+        DATE => "date",
+        /// Only meaningful for HTTP/1.0 clients asking to keep a connection.
+        KEEP_ALIVE => "keep-alive",
+        TRAILER => "trailer",
+        TRANSFER_ENCODING => "transfer-encoding",
+        UPGRADE => "upgrade",
+        VIA => "via",
+    }
+}
+
+/// Fields describing the representation carried in the body, RFC 9110 §8.
+pub mod representation {
+    use super::*;
+
+    header_names! {
+        CONTENT_ENCODING => "content-encoding",
+        CONTENT_LANGUAGE => "content-language",
+        CONTENT_LENGTH => "content-length",
+        CONTENT_LOCATION => "content-location",
+        CONTENT_RANGE => "content-range",
+        CONTENT_TYPE => "content-type",
+        ETAG => "etag",
+        LAST_MODIFIED => "last-modified",
+    }
+}
+
+/// Fields a server sends about the response, RFC 9110 §10.2.
+pub mod response {
+    use super::*;
+
+    header_names! {
+        ACCEPT_RANGES => "accept-ranges",
+        ALLOW => "allow",
+        LOCATION => "location",
+        RETRY_AFTER => "retry-after",
+        SERVER => "server",
+        SET_COOKIE => "set-cookie",
+        VARY => "vary",
+        WWW_AUTHENTICATE => "www-authenticate",
+    }
+}
+
+#[test]
+fn test_header_name() {
+    let name = HeaderName::parse(b"Content-Type").ok().unwrap();
+    assert_eq!(name, representation::CONTENT_TYPE);
+    assert_eq!(name.to_string(), "content-type");
+    assert_eq!(
+        HeaderName::parse(b"Bad Name").err(),
+        Some(RequestError::InvalidHeaderName {
+            offset: 3,
+            found: ' '
+        })
+    );
+}
+
+#[test]
+fn test_header_value() {
+    let value = HeaderValue::parse(b"text/html; q=0.9").ok().unwrap();
+    assert_eq!(value.to_str(), Some("text/html; q=0.9"));
+
+    let value = HeaderValue::parse(b"caf\xe9").ok().unwrap();
+    assert_eq!(value.to_str(), None);
+    assert_eq!(value.as_bytes(), b"caf\xe9");
+
+    assert_eq!(
+        HeaderValue::parse(b"a\r\nb").err(),
+        Some(RequestError::InvalidHeaderValue {
+            offset: 1,
+            found: '\r'
+        })
+    );
+}
+
+#[cfg(test)]
+fn value(text: &str) -> HeaderValue {
+    text.parse().ok().unwrap()
+}
+
+#[test]
+fn test_header_map_multiple_values() {
+    let mut map = HeaderMap::new();
+    map.append(request::ACCEPT, value("text/html"));
+    map.append(general::VIA, value("1.1 proxy"));
+    map.append("Accept".parse().ok().unwrap(), value("application/json"));
+
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.get("ACCEPT").unwrap(), "text/html");
+    assert_eq!(map.get_all(request::ACCEPT).count(), 2);
+    assert_eq!(
+        map.get_joined(&request::ACCEPT).unwrap(),
+        "text/html, application/json"
+    );
+    assert_eq!(
+        map.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>(),
+        ["accept", "via", "accept"]
+    );
+    assert_eq!(
+        map.to_string(),
+        "accept: text/html\r\nvia: 1.1 proxy\r\naccept: application/json\r\n"
+    );
+}
+
+#[test]
+fn test_header_map_insert_and_remove() {
+    let mut map = HeaderMap::new();
+    map.append(request::ACCEPT, value("a"));
+    map.append(general::VIA, value("v"));
+    map.append(request::ACCEPT, value("b"));
+
+    let old = map.insert(request::ACCEPT, value("c"));
+    assert_eq!(old.unwrap(), "a");
+    assert_eq!(
+        map.iter()
+            .map(|(n, v)| (n.as_str(), v.to_str().unwrap()))
+            .collect::<Vec<_>>(),
+        [("accept", "c"), ("via", "v")]
+    );
+    assert!(map.insert(request::HOST, value("h")).is_none());
+
+    assert_eq!(map.remove("Via").unwrap(), "v");
+    assert!(!map.contains_key(general::VIA));
+    assert!(map.remove(general::VIA).is_none());
+    assert_eq!(map.len(), 2);
+}
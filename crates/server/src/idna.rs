@@ -0,0 +1,276 @@
+use crate::UriError;
+
+// Punycode parameters, RFC 3492 §5.
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 0x80;
+
+/// The ACE prefix that marks a punycode label, RFC 5890 §2.3.2.5.
+const ACE_PREFIX: &str = "xn--";
+
+/// The longest label DNS allows, RFC 1034 §3.1.
+const MAX_LABEL_LENGTH: usize = 63;
+
+/// Converts an internationalized domain name to its ASCII form, so
+/// `bücher.example` becomes `xn--bcher-kva.example`.
+///
+/// Labels are lowercased and every label with non-ASCII characters is
+/// punycode-encoded. This covers the common cases of IDNA2008 without the
+/// Unicode mapping and normalization tables; input is expected in NFC, as
+/// typed by users. Offsets in errors are byte offsets into `domain`.
+pub fn domain_to_ascii(domain: &str) -> Result<String, UriError> {
+    let mut out = String::with_capacity(domain.len());
+    let mut offset = 0;
+
+    for (i, label) in domain.split(is_label_separator).enumerate() {
+        if i > 0 {
+            out.push('.');
+        }
+        let label_start = offset;
+        // Every separator is a single char, so step over it by its width.
+        offset += label.len();
+        offset += domain[offset..].chars().next().map_or(0, char::len_utf8);
+
+        if label.is_ascii() {
+            out.push_str(label);
+            continue;
+        }
+
+        if let Some((at, found)) = label
+            .char_indices()
+            .find(|(_, c)| c.is_whitespace() || c.is_control())
+        {
+            return Err(UriError::InvalidHostChar {
+                offset: label_start + at,
+                found,
+            });
+        }
+
+        let lowered: String = label.chars().flat_map(char::to_lowercase).collect();
+        let encoded = match punycode_encode(&lowered) {
+            Some(encoded) if ACE_PREFIX.len() + encoded.len() <= MAX_LABEL_LENGTH => encoded,
+            _ => {
+                return Err(UriError::InvalidIdn {
+                    offset: label_start,
+                })
+            }
+        };
+        out.push_str(ACE_PREFIX);
+        out.push_str(&encoded);
+    }
+
+    Ok(out)
+}
+
+/// Converts the `xn--` labels of a domain back to Unicode for display.
+/// Labels that are not valid punycode are left as they are.
+pub fn domain_to_unicode(domain: &str) -> String {
+    let labels: Vec<String> = domain
+        .split('.')
+        .map(|label| {
+            let decoded = match label.get(..ACE_PREFIX.len()) {
+                Some(prefix) if prefix.eq_ignore_ascii_case(ACE_PREFIX) => {
+                    punycode_decode(&label[ACE_PREFIX.len()..])
+                }
+                _ => None,
+            };
+            decoded.unwrap_or_else(|| String::from(label))
+        })
+        .collect();
+    labels.join(".")
+}
+
+/// Full stops that UTS #46 maps to '.'.
+fn is_label_separator(c: char) -> bool {
+    matches!(c, '.' | '\u{3002}' | '\u{FF0E}' | '\u{FF61}')
+}
+
+/// Bias adaptation, RFC 3492 §6.1.
+fn adapt(delta: u32, num_points: u32, first_time: bool) -> u32 {
+    let mut delta = if first_time { delta / DAMP } else { delta / 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (((BASE - T_MIN + 1) * delta) / (delta + SKEW))
+}
+
+fn threshold(k: u32, bias: u32) -> u32 {
+    if k <= bias {
+        T_MIN
+    } else if k >= bias + T_MAX {
+        T_MAX
+    } else {
+        k - bias
+    }
+}
+
+fn encode_digit(d: u32) -> char {
+    // 0..=25 are 'a'..='z', 26..=35 are '0'..='9'.
+    let byte = if d < 26 {
+        b'a' + d as u8
+    } else {
+        b'0' + (d - 26) as u8
+    };
+    byte as char
+}
+
+fn decode_digit(c: char) -> Option<u32> {
+    match c {
+        'a'..='z' => Some(c as u32 - 'a' as u32),
+        'A'..='Z' => Some(c as u32 - 'A' as u32),
+        '0'..='9' => Some(c as u32 - '0' as u32 + 26),
+        _ => None,
+    }
+}
+
+/// Punycode encoding, RFC 3492 §6.3. `None` on overflow.
+pub(crate) fn punycode_encode(input: &str) -> Option<String> {
+    let code_points: Vec<u32> = input.chars().map(|c| c as u32).collect();
+    let mut out: String = input.chars().filter(char::is_ascii).collect();
+
+    let basic = out.len() as u32;
+    let mut handled = basic;
+    if basic > 0 {
+        out.push('-');
+    }
+
+    let mut n = INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+
+    while (handled as usize) < code_points.len() {
+        let m = code_points.iter().copied().filter(|&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+
+        for &c in &code_points {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    out.push(encode_digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                out.push(encode_digit(q));
+                bias = adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta = delta.checked_add(1)?;
+        n = n.checked_add(1)?;
+    }
+
+    Some(out)
+}
+
+/// Punycode decoding, RFC 3492 §6.2. `None` for malformed input.
+pub(crate) fn punycode_decode(input: &str) -> Option<String> {
+    let (basic, extended) = match input.rfind('-') {
+        Some(idx) => (&input[..idx], &input[idx + 1..]),
+        None => ("", input),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+
+    let mut out: Vec<char> = basic.chars().collect();
+    let mut n = INITIAL_N;
+    let mut i: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut digits = extended.chars().peekable();
+
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut w: u32 = 1;
+        let mut k = BASE;
+        loop {
+            let digit = decode_digit(digits.next()?)?;
+            i = i.checked_add(digit.checked_mul(w)?)?;
+            let t = threshold(k, bias);
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let len = out.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        // Basic code points must not be encoded.
+        if n < INITIAL_N {
+            return None;
+        }
+        out.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+
+    Some(out.into_iter().collect())
+}
+
+#[test]
+fn test_punycode_rfc_samples() {
+    // RFC 3492 §7.1 (B) Chinese (simplified) and (L) Japanese.
+    let samples = [
+        ("他们为什么不说中文", "ihqwcrb4cv8a8dqg056pqjye"),
+        ("3年B組金八先生", "3B-ww4c5e180e575a65lsy2b"),
+        ("bücher", "bcher-kva"),
+    ];
+    for (unicode, encoded) in samples {
+        assert_eq!(punycode_encode(unicode).as_deref(), Some(encoded));
+        assert_eq!(punycode_decode(encoded).as_deref(), Some(unicode));
+    }
+}
+
+#[test]
+fn test_domain_to_ascii() {
+    assert_eq!(
+        domain_to_ascii("Bücher.example").ok().unwrap(),
+        "xn--bcher-kva.example"
+    );
+    assert_eq!(
+        domain_to_ascii("münchen。de").ok().unwrap(),
+        "xn--mnchen-3ya.de"
+    );
+    assert_eq!(
+        domain_to_ascii("Telemakos.io").ok().unwrap(),
+        "Telemakos.io"
+    );
+    assert_eq!(
+        domain_to_ascii("a.b\u{3000}c.example").err(),
+        Some(UriError::InvalidHostChar {
+            offset: 3,
+            found: '\u{3000}'
+        })
+    );
+    assert_eq!(
+        domain_to_ascii(&"ü".repeat(60)).err(),
+        Some(UriError::InvalidIdn { offset: 0 })
+    );
+}
+
+#[test]
+fn test_domain_to_unicode() {
+    assert_eq!(domain_to_unicode("xn--bcher-kva.example"), "bücher.example");
+    assert_eq!(domain_to_unicode("XN--bcher-kva.io"), "bücher.io");
+    assert_eq!(
+        domain_to_unicode("xn--99999999999.example"),
+        "xn--99999999999.example"
+    );
+}
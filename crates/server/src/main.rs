@@ -1,33 +1,107 @@
-use server::{HttpSchemeEnum, Tag, Tokenizer, Uri};
-
-fn main() {
-    let test_uri = String::from("https://telemakos.io/test?test_query=#fragment-here");
-    let mut tokenizer = Tokenizer::new(test_uri.clone());
-
-    println!("Tokenizing . . . \n{}", tokenizer);
-
-    println!("Parsing {} . . .", test_uri);
-
-    println!("{}", Uri::parse_tokens(&mut tokenizer).ok().unwrap());
-
-    // loop {``
-    //     let token = tokenizer.next();
-    //     if token.tag() == Tag::EndOfURI {
-    //         break;
-    //     }
-    //     println!("{:?}", token.tag());
-    // }
-    // let address = "127.0.0.1:8080";
-    // println!("Opening listener on {} . . .", address);
-    // let stream = TcpListener::bind(address)?;
-
-    // for s in stream.incoming() {
-    //     println!("Received a stream: {:?}", &s);
-    //     let mut buffer = [0; 8000];
-    //     s?.peek(&mut buffer)?;
-    //     let converted_buffer = Uri::parse_buffer(&buffer);
-    //     println!("Received:\n\t{:?}", converted_buffer);
-    // }
-    // stream.write(&[1])?;
-    // Ok(())
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use server::{
+    Body, HttpResponse, ParseStatus, RequestError, RequestOptions, RequestParser, StatusCode,
+};
+
+/// How long a connection may go without sending anything before it is
+/// dropped, so idle clients do not hold their thread forever.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn main() -> std::io::Result<()> {
+    let address = "127.0.0.1:8080";
+    println!("Opening listener on {} . . .", address);
+    let listener = TcpListener::bind(address)?;
+
+    // Each connection gets its own thread, so a client that sends its
+    // request a byte at a time only holds up itself. A failed accept or a
+    // broken connection only ends that connection.
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Connection error: {}", e);
+                continue;
+            }
+        };
+        thread::spawn(move || {
+            if let Err(e) = handle(stream) {
+                eprintln!("Connection error: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Answers every request on the connection until the client closes it,
+/// asks for it to be closed or sends a malformed request.
+fn handle(mut stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let options = RequestOptions::default();
+    let mut parser = RequestParser::with_options(options);
+    // Heads and bodies are read from the same buffer, so bytes of the next
+    // pipelined request stay in it.
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        let length = chunk.len();
+
+        let request = match parser.feed(chunk) {
+            Ok(ParseStatus::NeedMore) => {
+                reader.consume(length);
+                continue;
+            }
+            Ok(ParseStatus::Complete(request, consumed)) => {
+                reader.consume(consumed);
+                request
+            }
+            // An oversized target is answered with 414, too large a
+            // head with 431, an unknown major version with 505 and
+            // anything else malformed with 400.
+            Err(e) => return reject(&mut stream, &e),
+        };
+
+        // Too large a body is answered with 413 and a transfer coding
+        // other than chunked with 501.
+        let mut body = match Body::new(&mut reader, request.framing(), options.max_body_length) {
+            Ok(body) => body,
+            Err(e) => return reject(&mut stream, &e),
+        };
+        // No handler reads bodies yet, but the body has to be consumed to
+        // find where the next request starts.
+        let body_length = match std::io::copy(&mut body, &mut std::io::sink()) {
+            Ok(length) => length,
+            Err(e) => match RequestError::from_io(&e) {
+                Some(e) => return reject(&mut stream, e),
+                None => return Err(e),
+            },
+        };
+
+        println!(
+            "{} {} ({} byte body)",
+            request.method(),
+            request.target(),
+            body_length
+        );
+        let response = HttpResponse::new(StatusCode::Ok)
+            .with_version(request.version())
+            .with_keep_alive(request.keep_alive());
+        response.write_to(&mut stream)?;
+        if !response.keep_alive() {
+            return Ok(());
+        }
+    }
+}
+
+fn reject(stream: &mut TcpStream, error: &RequestError) -> std::io::Result<()> {
+    eprintln!("Rejected request: {}", error);
+    HttpResponse::from(error).write_to(stream)
 }
@@ -0,0 +1,318 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{char_at, is_tchar, HeaderError};
+
+/// A media type such as `text/html; charset=utf-8`, RFC 9110 §8.3.1.
+///
+/// The type, subtype and parameter names are case-insensitive and stored
+/// lowercase. Parameter values keep their case, with any quoting removed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MediaType {
+    main_type: String,
+    subtype: String,
+    params: Vec<(String, String)>,
+}
+
+impl MediaType {
+    /// media-type = type "/" subtype parameters
+    pub fn parse(input: &str) -> Result<Self, HeaderError> {
+        let mut cursor = Cursor::new(input);
+        cursor.skip_ows();
+        let media_type = cursor.media_type(|_| false)?.0;
+        cursor.skip_ows();
+        cursor.end()?;
+        Ok(media_type)
+    }
+
+    /// The type before the '/', such as `text`.
+    pub fn main_type(&self) -> &str {
+        &self.main_type
+    }
+
+    pub fn subtype(&self) -> &str {
+        &self.subtype
+    }
+
+    /// Every parameter in the order given.
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    /// The value of a parameter, looked up case-insensitively.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v)
+    }
+
+    /// The charset parameter, RFC 9110 §8.3.2.
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+
+    /// Whether `type/subtype` match, ignoring parameters.
+    pub fn essence_eq(&self, other: &MediaType) -> bool {
+        self.main_type == other.main_type && self.subtype == other.subtype
+    }
+}
+
+impl FromStr for MediaType {
+    type Err = HeaderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MediaType::parse(s)
+    }
+}
+
+/// Writes parameter values as tokens where they can be and as
+/// quoted-strings otherwise.
+impl Display for MediaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.main_type, self.subtype)?;
+        for (name, value) in &self.params {
+            if !value.is_empty() && value.bytes().all(is_tchar) {
+                write!(f, ";{}={}", name, value)?;
+            } else {
+                write!(f, ";{}=\"", name)?;
+                for c in value.chars() {
+                    if c == '"' || c == '\\' {
+                        write!(f, "\\")?;
+                    }
+                    write!(f, "{}", c)?;
+                }
+                write!(f, "\"")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the shared pieces of header field grammar, RFC 9110 §5.6, from a
+/// field value.
+pub(crate) struct Cursor<'a> {
+    input: &'a str,
+    index: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self { input, index: 0 }
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Moves back to an index read earlier.
+    pub(crate) fn reset(&mut self, index: usize) {
+        self.index = index;
+    }
+
+    pub(crate) fn is_end(&self) -> bool {
+        self.index == self.input.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.index).copied()
+    }
+
+    /// The error for the byte at the cursor.
+    pub(crate) fn unexpected(&self) -> HeaderError {
+        match self.peek() {
+            Some(_) => HeaderError::InvalidChar {
+                offset: self.index,
+                found: char_at(self.input.as_bytes(), self.index),
+            },
+            None => HeaderError::UnexpectedEnd { offset: self.index },
+        }
+    }
+
+    /// OWS = *( SP / HTAB )
+    pub(crate) fn skip_ows(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.index += 1;
+        }
+    }
+
+    /// Consumes `b` if it is next.
+    pub(crate) fn eat(&mut self, b: u8) -> bool {
+        let found = self.peek() == Some(b);
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    pub(crate) fn expect(&mut self, b: u8) -> Result<(), HeaderError> {
+        match self.eat(b) {
+            true => Ok(()),
+            false => Err(self.unexpected()),
+        }
+    }
+
+    pub(crate) fn end(&self) -> Result<(), HeaderError> {
+        match self.is_end() {
+            true => Ok(()),
+            false => Err(self.unexpected()),
+        }
+    }
+
+    /// token = 1*tchar
+    pub(crate) fn token(&mut self) -> Result<&'a str, HeaderError> {
+        let start = self.index;
+        while self.peek().is_some_and(is_tchar) {
+            self.index += 1;
+        }
+        match self.index > start {
+            true => Ok(&self.input[start..self.index]),
+            false => Err(self.unexpected()),
+        }
+    }
+
+    /// quoted-string = DQUOTE *( qdtext / quoted-pair ) DQUOTE
+    ///
+    /// Returns the text with the quotes and escapes removed.
+    fn quoted_string(&mut self) -> Result<String, HeaderError> {
+        self.expect(b'"')?;
+        let mut text = String::new();
+        loop {
+            let c = match self.input[self.index..].chars().next() {
+                Some(c) => c,
+                None => return Err(self.unexpected()),
+            };
+            match c {
+                '"' => {
+                    self.index += 1;
+                    return Ok(text);
+                }
+                '\\' => {
+                    self.index += 1;
+                    match self.input[self.index..].chars().next() {
+                        Some(c) if c == '\t' || !c.is_control() => {
+                            text.push(c);
+                            self.index += c.len_utf8();
+                        }
+                        _ => return Err(self.unexpected()),
+                    }
+                }
+                c if c == '\t' || !c.is_control() => {
+                    text.push(c);
+                    self.index += c.len_utf8();
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    /// parameter-value = ( token / quoted-string )
+    pub(crate) fn param_value(&mut self) -> Result<String, HeaderError> {
+        match self.peek() {
+            Some(b'"') => self.quoted_string(),
+            _ => self.token().map(String::from),
+        }
+    }
+
+    /// Reads `type/subtype` and its parameters.
+    ///
+    /// Reading stops before the first parameter `stop` accepts, for the
+    /// weight that ends the parameters of a media-range in Accept. Returns
+    /// the media type and whether it stopped there.
+    pub(crate) fn media_type(
+        &mut self,
+        stop: impl Fn(&str) -> bool,
+    ) -> Result<(MediaType, bool), HeaderError> {
+        let main_type = self.token()?.to_ascii_lowercase();
+        self.expect(b'/')?;
+        let subtype = self.token()?.to_ascii_lowercase();
+        let (params, stopped) = self.params(stop)?;
+        Ok((
+            MediaType {
+                main_type,
+                subtype,
+                params,
+            },
+            stopped,
+        ))
+    }
+
+    /// parameters = *( OWS ";" OWS [ parameter ] )
+    ///
+    /// parameter = parameter-name "=" parameter-value
+    fn params(
+        &mut self,
+        stop: impl Fn(&str) -> bool,
+    ) -> Result<(Vec<(String, String)>, bool), HeaderError> {
+        let mut params = vec![];
+        loop {
+            let before = self.index;
+            self.skip_ows();
+            if !self.eat(b';') {
+                self.index = before;
+                return Ok((params, false));
+            }
+            self.skip_ows();
+            // An empty parameter, as in "text/html;", is allowed.
+            if !self.peek().is_some_and(is_tchar) {
+                continue;
+            }
+            let name_start = self.index;
+            let name = self.token()?.to_ascii_lowercase();
+            if stop(&name) {
+                self.index = name_start;
+                return Ok((params, true));
+            }
+            self.expect(b'=')?;
+            params.push((name, self.param_value()?));
+        }
+    }
+}
+
+#[test]
+fn test_media_type_parse() {
+    let media_type = MediaType::parse("Text/HTML; Charset=\"UTF-8\" ;level=1")
+        .ok()
+        .unwrap();
+
+    assert_eq!(media_type.main_type(), "text");
+    assert_eq!(media_type.subtype(), "html");
+    assert_eq!(media_type.charset(), Some("UTF-8"));
+    assert_eq!(media_type.param("LEVEL"), Some("1"));
+    assert_eq!(media_type.to_string(), "text/html;charset=UTF-8;level=1");
+}
+
+#[test]
+fn test_media_type_quoting() {
+    let media_type = MediaType::parse(r#"multipart/form-data; boundary="a \"b\", c""#)
+        .ok()
+        .unwrap();
+
+    assert_eq!(media_type.param("boundary"), Some(r#"a "b", c"#));
+    assert_eq!(
+        media_type.to_string(),
+        r#"multipart/form-data;boundary="a \"b\", c""#
+    );
+}
+
+#[test]
+fn test_media_type_errors() {
+    assert_eq!(
+        MediaType::parse("text").err(),
+        Some(HeaderError::UnexpectedEnd { offset: 4 })
+    );
+    assert_eq!(
+        MediaType::parse("text/html; charset").err(),
+        Some(HeaderError::UnexpectedEnd { offset: 18 })
+    );
+    assert_eq!(
+        MediaType::parse("text/html; a=\"open").err(),
+        Some(HeaderError::UnexpectedEnd { offset: 18 })
+    );
+    assert_eq!(
+        MediaType::parse("text/html extra").err(),
+        Some(HeaderError::InvalidChar {
+            offset: 10,
+            found: 'e'
+        })
+    );
+}
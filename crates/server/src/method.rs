@@ -0,0 +1,183 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{char_at, is_tchar, RequestError};
+
+/// A request method, RFC 9110 §9. Methods are case-sensitive, so `get` is
+/// an extension method and not `GET`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HttpMethodEnum {
+    GET,
+    HEAD,
+    POST,
+    PUT,
+    DELETE,
+    OPTIONS,
+    TRACE,
+    CONNECT,
+    PATCH,
+    /// Any other method. Holds a validated token.
+    Extension(String),
+}
+
+impl HttpMethodEnum {
+    /// Parses a method from the bytes of a request line.
+    ///
+    /// method = token
+    pub fn parse(bytes: &[u8]) -> Result<Self, RequestError> {
+        if bytes.is_empty() {
+            return Err(RequestError::InvalidMethod {
+                offset: 0,
+                found: '\0',
+            });
+        }
+        if let Some(offset) = bytes.iter().position(|b| !is_tchar(*b)) {
+            return Err(RequestError::InvalidMethod {
+                offset,
+                found: char_at(bytes, offset),
+            });
+        }
+
+        Ok(match bytes {
+            b"GET" => HttpMethodEnum::GET,
+            b"HEAD" => HttpMethodEnum::HEAD,
+            b"POST" => HttpMethodEnum::POST,
+            b"PUT" => HttpMethodEnum::PUT,
+            b"DELETE" => HttpMethodEnum::DELETE,
+            b"OPTIONS" => HttpMethodEnum::OPTIONS,
+            b"TRACE" => HttpMethodEnum::TRACE,
+            b"CONNECT" => HttpMethodEnum::CONNECT,
+            b"PATCH" => HttpMethodEnum::PATCH,
+            // tchar is ASCII, so the token is valid UTF-8.
+            _ => HttpMethodEnum::Extension(String::from_utf8_lossy(bytes).into_owned()),
+        })
+    }
+
+    /// The method name as sent in a request line.
+    pub fn as_str(&self) -> &str {
+        match self {
+            HttpMethodEnum::GET => "GET",
+            HttpMethodEnum::HEAD => "HEAD",
+            HttpMethodEnum::POST => "POST",
+            HttpMethodEnum::PUT => "PUT",
+            HttpMethodEnum::DELETE => "DELETE",
+            HttpMethodEnum::OPTIONS => "OPTIONS",
+            HttpMethodEnum::TRACE => "TRACE",
+            HttpMethodEnum::CONNECT => "CONNECT",
+            HttpMethodEnum::PATCH => "PATCH",
+            HttpMethodEnum::Extension(token) => token,
+        }
+    }
+
+    /// Read-only methods, RFC 9110 §9.2.1. Extension methods are assumed
+    /// unsafe.
+    pub fn is_safe(&self) -> bool {
+        matches!(
+            self,
+            HttpMethodEnum::GET
+                | HttpMethodEnum::HEAD
+                | HttpMethodEnum::OPTIONS
+                | HttpMethodEnum::TRACE
+        )
+    }
+
+    /// Methods whose intended effect is the same for one request or many,
+    /// RFC 9110 §9.2.2. Safe methods are idempotent too.
+    pub fn is_idempotent(&self) -> bool {
+        self.is_safe() || matches!(self, HttpMethodEnum::PUT | HttpMethodEnum::DELETE)
+    }
+
+    /// Methods whose responses may be stored, RFC 9110 §9.2.3. POST
+    /// responses are only reusable with explicit freshness information.
+    pub fn is_cacheable(&self) -> bool {
+        matches!(
+            self,
+            HttpMethodEnum::GET | HttpMethodEnum::HEAD | HttpMethodEnum::POST
+        )
+    }
+}
+
+impl FromStr for HttpMethodEnum {
+    type Err = RequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HttpMethodEnum::parse(s.as_bytes())
+    }
+}
+
+impl Display for HttpMethodEnum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[test]
+fn test_method_parse() {
+    assert_eq!(
+        HttpMethodEnum::parse(b"GET").ok(),
+        Some(HttpMethodEnum::GET)
+    );
+    assert_eq!(
+        HttpMethodEnum::parse(b"PATCH").ok(),
+        Some(HttpMethodEnum::PATCH)
+    );
+    assert_eq!(
+        "PROPFIND".parse::<HttpMethodEnum>().ok(),
+        Some(HttpMethodEnum::Extension(String::from("PROPFIND")))
+    );
+    // Case matters, so this is an extension method.
+    assert_eq!(
+        HttpMethodEnum::parse(b"get").ok(),
+        Some(HttpMethodEnum::Extension(String::from("get")))
+    );
+    assert_eq!(HttpMethodEnum::CONNECT.to_string(), "CONNECT");
+}
+
+#[test]
+fn test_method_parse_rejects_non_tokens() {
+    assert_eq!(
+        HttpMethodEnum::parse(b"GE T").err(),
+        Some(RequestError::InvalidMethod {
+            offset: 2,
+            found: ' '
+        })
+    );
+    assert_eq!(
+        HttpMethodEnum::parse(b"G\xffT").err(),
+        Some(RequestError::InvalidMethod {
+            offset: 1,
+            found: '\u{FFFD}'
+        })
+    );
+    assert!(HttpMethodEnum::parse(b"").is_err());
+    assert!(HttpMethodEnum::parse(b"GET(").is_err());
+}
+
+#[test]
+fn test_method_properties() {
+    use HttpMethodEnum::*;
+
+    let safe: Vec<bool> = [GET, HEAD, POST, PUT, DELETE, OPTIONS, TRACE, CONNECT, PATCH]
+        .iter()
+        .map(|m| m.is_safe())
+        .collect();
+    assert_eq!(
+        safe,
+        [true, true, false, false, false, true, true, false, false]
+    );
+
+    assert!(PUT.is_idempotent());
+    assert!(DELETE.is_idempotent());
+    assert!(OPTIONS.is_idempotent());
+    assert!(!POST.is_idempotent());
+    assert!(!PATCH.is_idempotent());
+
+    assert!(GET.is_cacheable());
+    assert!(POST.is_cacheable());
+    assert!(!PUT.is_cacheable());
+
+    let extension = Extension(String::from("PURGE"));
+    assert!(!extension.is_safe());
+    assert!(!extension.is_idempotent());
+    assert!(!extension.is_cacheable());
+}
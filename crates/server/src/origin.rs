@@ -0,0 +1,130 @@
+use std::fmt::Display;
+
+use crate::{Host, HttpSchemeEnum, Tokenizer, Uri, UriError};
+
+/// The origin of a URI: scheme, host and port, with the host normalized to
+/// lowercase and the port always filled in. Two URIs share an origin when
+/// their origins compare equal. Schemes without ports, like `file`, have
+/// no port in their origin.
+///
+/// `https://Example.Com/happy.js` has the origin `https://example.com:443`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Origin {
+    scheme: HttpSchemeEnum,
+    host: Host,
+    port: Option<u16>,
+}
+
+impl Origin {
+    pub fn new(scheme: HttpSchemeEnum, host: Host, port: Option<u16>) -> Self {
+        Self {
+            scheme,
+            host: host.normalize(),
+            port,
+        }
+    }
+
+    /// Parses a serialized origin, as sent in an `Origin` header. Anything
+    /// beyond scheme, host and port is rejected.
+    pub fn parse(input: &str) -> Result<Self, UriError> {
+        let mut tokenizer = Tokenizer::new(String::from(input));
+        let uri = Uri::parse_tokens(&mut tokenizer)?;
+
+        if !uri.has_authority()
+            || uri.username().is_some()
+            || !uri.path().is_empty()
+            || uri.query().is_some()
+            || uri.fragment().is_some()
+        {
+            return Err(UriError::NotAnOrigin);
+        }
+
+        Ok(uri.origin())
+    }
+
+    pub fn scheme(&self) -> &HttpSchemeEnum {
+        &self.scheme
+    }
+
+    pub fn host(&self) -> &Host {
+        &self.host
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+}
+
+impl From<&Uri> for Origin {
+    fn from(uri: &Uri) -> Self {
+        Origin::new(uri.scheme().clone(), uri.host().clone(), uri.port())
+    }
+}
+
+/// Serializes as the normalized URI prefix with the port always present.
+impl Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}", self.scheme.as_str(), self.host)?;
+        match self.port {
+            Some(port) => write!(f, ":{}", port),
+            None => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_origin_from_uri() {
+    let mut tokenizer = Tokenizer::new(String::from("https://Example.Com/happy.js"));
+    let uri = Uri::parse_tokens(&mut tokenizer).ok().unwrap();
+
+    let origin = uri.origin();
+    assert_eq!(origin.scheme(), &HttpSchemeEnum::HTTPS);
+    assert_eq!(origin.host(), "example.com");
+    assert_eq!(origin.port(), Some(443));
+    assert_eq!(origin.to_string(), "https://example.com:443");
+}
+
+#[test]
+fn test_origin_equality() {
+    let a = Origin::parse("http://TELEMAKOS.io").ok().unwrap();
+    let b = Origin::parse("http://telemakos.io:80").ok().unwrap();
+    let c = Origin::parse("https://telemakos.io").ok().unwrap();
+    let d = Origin::parse("http://telemakos.io:8080").ok().unwrap();
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_ne!(a, d);
+
+    let origins: std::collections::HashSet<Origin> = [a, b, c, d].into_iter().collect();
+    assert_eq!(origins.len(), 3);
+}
+
+#[test]
+fn test_origin_ip_hosts() {
+    let origin = Origin::parse("http://[::1]:8080").ok().unwrap();
+    assert_eq!(origin.to_string(), "http://[::1]:8080");
+
+    let origin = Origin::parse("https://127.0.0.1").ok().unwrap();
+    assert_eq!(origin.to_string(), "https://127.0.0.1:443");
+}
+
+#[test]
+fn test_origin_parse_rejects_extra_components() {
+    assert_eq!(
+        Origin::parse("https://telemakos.io/path"),
+        Err(UriError::NotAnOrigin)
+    );
+    assert_eq!(
+        Origin::parse("https://telemakos.io?q"),
+        Err(UriError::NotAnOrigin)
+    );
+    assert_eq!(
+        Origin::parse("https://user@telemakos.io"),
+        Err(UriError::NotAnOrigin)
+    );
+    assert_eq!(
+        Origin::parse("https://telemakos.io#x"),
+        Err(UriError::NotAnOrigin)
+    );
+    assert_eq!(Origin::parse("urn:"), Err(UriError::NotAnOrigin));
+}
@@ -0,0 +1,223 @@
+use crate::{HttpRequest, Location, RequestError, RequestOptions};
+
+/// What [`RequestParser::feed`] made of the bytes so far.
+// The status is returned by value and matched at once, so boxing the
+// request would only add an allocation.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum ParseStatus {
+    /// The head is not finished; feed the next chunk when it arrives.
+    NeedMore,
+    /// A full request head, and how many bytes of the last chunk it used.
+    /// The rest of that chunk is a body or the next pipelined request.
+    Complete(HttpRequest, usize),
+}
+
+/// A request parser that is fed bytes as they come off the socket.
+///
+/// Every call only scans the new bytes for line ends, recording each line
+/// as a [`Location`] in the buffered head, so input that trickles in a
+/// byte at a time costs no more than input that arrives at once. The lines
+/// are parsed once the empty line that ends the head is seen. After a
+/// complete request or an error the parser is ready for the next request.
+///
+/// Offsets in errors are byte offsets from the start of the request.
+#[derive(Debug, Default)]
+pub struct RequestParser {
+    options: RequestOptions,
+    buffer: Vec<u8>,
+    scan: HeadScan,
+}
+
+impl RequestParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: RequestOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Adds `chunk` to the buffered head. Returns [`ParseStatus::NeedMore`]
+    /// until the head is complete.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<ParseStatus, RequestError> {
+        let offset = self.buffer.len();
+        self.buffer.extend_from_slice(chunk);
+
+        let end = match self.scan.scan(&self.buffer, self.options.max_head_length) {
+            Ok(Some(end)) => end,
+            Ok(None) => return Ok(ParseStatus::NeedMore),
+            Err(e) => {
+                self.reset();
+                return Err(e);
+            }
+        };
+
+        let request = HttpRequest::from_lines(&self.buffer, self.scan.lines(), &self.options);
+        self.reset();
+        Ok(ParseStatus::Complete(request?, end - offset))
+    }
+
+    /// How many bytes of an unfinished head are buffered.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.scan = HeadScan::default();
+    }
+}
+
+/// Finds the lines of a request head, resuming where the last scan of the
+/// same, grown buffer stopped.
+#[derive(Debug, Default)]
+pub(crate) struct HeadScan {
+    /// Where the line being scanned starts.
+    line_start: usize,
+    /// Where to resume looking for its LF.
+    searched: usize,
+    lines: Vec<Location>,
+}
+
+impl HeadScan {
+    /// Scans the bytes not seen yet. Returns the index just past the empty
+    /// line that ends the head once it is found.
+    ///
+    /// Lines end at LF, with an optional CR before it. Empty lines before
+    /// the request-line are skipped, RFC 9112 §2.2.
+    pub(crate) fn scan(
+        &mut self,
+        bytes: &[u8],
+        limit: usize,
+    ) -> Result<Option<usize>, RequestError> {
+        while let Some(idx) = bytes[self.searched..].iter().position(|b| *b == b'\n') {
+            let newline = self.searched + idx;
+            let end = match bytes[self.line_start..newline].last() {
+                Some(b'\r') => newline - 1,
+                _ => newline,
+            };
+            let line = Location::new(self.line_start, end);
+            self.line_start = newline + 1;
+            self.searched = newline + 1;
+
+            if self.line_start > limit {
+                return Err(RequestError::HeadTooLarge { limit });
+            }
+            match (line.start() == line.end(), self.lines.is_empty()) {
+                (true, true) => continue,
+                (true, false) => return Ok(Some(self.line_start)),
+                (false, _) => self.lines.push(line),
+            }
+        }
+
+        self.searched = bytes.len();
+        if bytes.len() > limit {
+            return Err(RequestError::HeadTooLarge { limit });
+        }
+        Ok(None)
+    }
+
+    /// The request-line and field lines found so far, without terminators.
+    pub(crate) fn lines(&self) -> &[Location] {
+        &self.lines
+    }
+}
+
+#[cfg(test)]
+fn feed_bytewise(parser: &mut RequestParser, input: &[u8]) -> Vec<(HttpRequest, usize)> {
+    let mut complete = vec![];
+    for (idx, byte) in input.iter().enumerate() {
+        if let ParseStatus::Complete(request, consumed) = parser.feed(&[*byte]).ok().unwrap() {
+            complete.push((request, idx + consumed));
+        }
+    }
+    complete
+}
+
+#[test]
+fn test_parser_byte_at_a_time() {
+    let input = b"GET /slow?q=1 HTTP/1.1\r\nHost: telemakos.io\r\nX-A: b\r\n\r\n";
+    let mut parser = RequestParser::new();
+
+    let complete = feed_bytewise(&mut parser, input);
+    assert_eq!(complete.len(), 1);
+    let (request, end) = &complete[0];
+    assert_eq!(*end, input.len());
+    assert_eq!(request.target().query(), Some("q=1"));
+    assert_eq!(request.headers().get("x-a").unwrap(), "b");
+    assert_eq!(parser.buffered(), 0);
+}
+
+#[test]
+fn test_parser_split_crlf() {
+    let mut parser = RequestParser::new();
+
+    assert!(matches!(
+        parser.feed(b"GET / HTTP/1.1\r").ok().unwrap(),
+        ParseStatus::NeedMore
+    ));
+    assert!(matches!(
+        parser.feed(b"\nHost: a\r\n\r").ok().unwrap(),
+        ParseStatus::NeedMore
+    ));
+    match parser.feed(b"\n").ok().unwrap() {
+        ParseStatus::Complete(request, consumed) => {
+            assert_eq!(consumed, 1);
+            assert_eq!(request.headers().get("host").unwrap(), "a");
+        }
+        ParseStatus::NeedMore => panic!("expected a complete request"),
+    }
+}
+
+#[test]
+fn test_parser_pipelined() {
+    let input = b"GET /one HTTP/1.1\r\nHost: a\r\n\r\nGET /two HTTP/1.1\r\nHost: a\r\n\r\nGET /th";
+    let mut parser = RequestParser::new();
+    let mut chunk = &input[..];
+    let mut paths = vec![];
+
+    while let ParseStatus::Complete(request, consumed) = parser.feed(chunk).ok().unwrap() {
+        paths.push(String::from(request.target().path().unwrap()));
+        chunk = &chunk[consumed..];
+    }
+    assert_eq!(paths, ["/one", "/two"]);
+    assert_eq!(parser.buffered(), 7);
+}
+
+#[test]
+fn test_parser_errors() {
+    let mut parser = RequestParser::new();
+    assert_eq!(
+        parser.feed(b"GET / HTTP/1.1\r\nHost : a\r\n\r\n").err(),
+        Some(RequestError::InvalidHeaderName {
+            offset: 20,
+            found: ' '
+        })
+    );
+    // The failed request is dropped and the next one starts clean.
+    assert!(matches!(
+        parser
+            .feed(b"GET / HTTP/1.1\r\nHost: a\r\n\r\n")
+            .ok()
+            .unwrap(),
+        ParseStatus::Complete(..)
+    ));
+
+    let mut parser = RequestParser::with_options(RequestOptions {
+        max_head_length: 32,
+        ..RequestOptions::default()
+    });
+    let mut result = Ok(ParseStatus::NeedMore);
+    for _ in 0..40 {
+        result = parser.feed(b"a");
+        if result.is_err() {
+            break;
+        }
+    }
+    assert_eq!(result.err(), Some(RequestError::HeadTooLarge { limit: 32 }));
+    assert_eq!(parser.buffered(), 0);
+}
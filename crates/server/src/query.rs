@@ -0,0 +1,221 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{check_percent_encoding, percent_decode_lossy, UriError};
+
+/// An ordered multimap of `application/x-www-form-urlencoded` query pairs.
+///
+/// Keys may repeat and keep the order they were parsed or appended in. A key
+/// written without '=' has an empty value. `Display` serializes the pairs
+/// back into a query string, without the leading '?'.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pairs: Vec<(String, String)>,
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self { pairs: vec![] }
+    }
+
+    /// Splits on '&', then on the first '='. '+' decodes to a space and
+    /// percent-escapes are decoded; a malformed escape is an error with its
+    /// offset in `input`. Escapes that are not UTF-8 decode to U+FFFD, as
+    /// in [`crate::Uri::path_segments`].
+    pub fn parse(input: &str) -> Result<Self, UriError> {
+        let mut query = Query::new();
+        let mut offset = 0;
+
+        for pair in input.split('&') {
+            let start = offset;
+            offset += pair.len() + 1;
+            if pair.is_empty() {
+                continue;
+            }
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let key = form_decode(key).map_err(|e| e.offset_by(start))?;
+            let value =
+                form_decode(value).map_err(|e| e.offset_by(start + pair.len() - value.len()))?;
+            query.pairs.push((key, value));
+        }
+
+        Ok(query)
+    }
+
+    /// The first value for `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every value for `key`, in order.
+    pub fn get_all(&self, key: &str) -> Vec<&str> {
+        self.pairs
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    /// Parses the first value for `key` as a `T`. `None` when the key is
+    /// missing.
+    pub fn get_as<T: FromStr>(&self, key: &str) -> Option<Result<T, T::Err>> {
+        self.get(key).map(str::parse)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.pairs.iter().any(|(k, _)| k == key)
+    }
+
+    /// Adds a pair after the existing ones, keeping any earlier values for
+    /// the same key.
+    pub fn append(&mut self, key: &str, value: &str) -> &mut Self {
+        self.pairs.push((String::from(key), String::from(value)));
+        self
+    }
+
+    /// Replaces every value for `key` with a single pair, placed where the
+    /// first one was, or appended if the key is new.
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self {
+        match self.pairs.iter().position(|(k, _)| k == key) {
+            Some(idx) => {
+                self.pairs[idx].1 = String::from(value);
+                let mut seen = 0;
+                self.pairs.retain(|(k, _)| {
+                    if k != key {
+                        return true;
+                    }
+                    seen += 1;
+                    seen == 1
+                });
+            }
+            None => {
+                self.append(key, value);
+            }
+        }
+        self
+    }
+
+    /// Removes every pair for `key`.
+    pub fn remove(&mut self, key: &str) -> &mut Self {
+        self.pairs.retain(|(k, _)| k != key);
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Query {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            pairs: iter
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        }
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        for (key, value) in &self.pairs {
+            if !out.is_empty() {
+                out.push('&');
+            }
+            form_encode(key, &mut out);
+            out.push('=');
+            form_encode(value, &mut out);
+        }
+        write!(f, "{}", out)
+    }
+}
+
+fn form_decode(input: &str) -> Result<String, UriError> {
+    check_percent_encoding(input)?;
+    // '+' is replaced before decoding so that an escaped "%2B" stays a '+'.
+    Ok(percent_decode_lossy(&input.replace('+', " ")))
+}
+
+/// The `application/x-www-form-urlencoded` byte serializer: ALPHA, DIGIT and
+/// `*-._` pass through, a space becomes '+', everything else is escaped.
+fn form_encode(input: &str, out: &mut String) {
+    for b in input.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => {
+                out.push(b as char)
+            }
+            b' ' => out.push('+'),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+}
+
+#[test]
+fn test_query_parse() {
+    let query = Query::parse("name=J%C3%BCrgen+Smith&tag=a&tag=b&flag&empty=&&page=3")
+        .ok()
+        .unwrap();
+
+    assert_eq!(query.get("name"), Some("Jürgen Smith"));
+    assert_eq!(query.get_all("tag"), vec!["a", "b"]);
+    assert_eq!(query.get("flag"), Some(""));
+    assert_eq!(query.get("empty"), Some(""));
+    assert_eq!(query.get("missing"), None);
+    assert_eq!(query.get_as::<u32>("page"), Some(Ok(3)));
+    assert!(query.get_as::<u32>("name").unwrap().is_err());
+    assert_eq!(query.len(), 6);
+}
+
+#[test]
+fn test_query_parse_plus_and_escapes() {
+    let query = Query::parse("a=1%2B1+%3D+2&b%20c=%26").ok().unwrap();
+
+    assert_eq!(query.get("a"), Some("1+1 = 2"));
+    assert_eq!(query.get("b c"), Some("&"));
+    assert_eq!(
+        Query::parse("a=1&b=%zz"),
+        Err(UriError::InvalidPercentEscape { offset: 6 })
+    );
+    assert_eq!(
+        Query::parse("a=%FF&%C3%BC").ok().unwrap().get("a"),
+        Some("\u{FFFD}")
+    );
+}
+
+#[test]
+fn test_query_serialize_round_trip() {
+    let mut query = Query::new();
+    query
+        .append("q", "rust http/1.1")
+        .append("lang", "en&de")
+        .append("q", "~tilde");
+
+    let serialized = query.to_string();
+    assert_eq!(serialized, "q=rust+http%2F1.1&lang=en%26de&q=%7Etilde");
+    assert_eq!(Query::parse(&serialized).ok().unwrap(), query);
+}
+
+#[test]
+fn test_query_set_and_remove() {
+    let mut query: Query = [("a", "1"), ("b", "2"), ("a", "3")].into_iter().collect();
+
+    query.set("a", "4");
+    assert_eq!(query.to_string(), "a=4&b=2");
+
+    query.set("c", "5").remove("b");
+    assert_eq!(query.to_string(), "a=4&c=5");
+    assert!(!query.contains_key("b"));
+}
@@ -0,0 +1,607 @@
+use crate::header::{general, request};
+use crate::parser::HeadScan;
+use crate::{
+    char_at, BodyFraming, HeaderMap, HeaderName, HeaderValue, Host, HttpMethodEnum, HttpVersion,
+    Location, RequestError, RequestTarget, DEFAULT_MAX_URI_LENGTH,
+};
+
+/// What to do with obs-fold, a header value continued on the next line by
+/// leading whitespace. RFC 9112 §5.2 lets a server either reject the
+/// request or replace each fold with a single space.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ObsFold {
+    #[default]
+    Reject,
+    Replace,
+}
+
+/// Limits and leniency for [`HttpRequest::parse_with`] and
+/// [`RequestParser`](crate::RequestParser).
+#[derive(Debug, Clone, Copy)]
+pub struct RequestOptions {
+    pub obs_fold: ObsFold,
+    /// The longest request-target accepted, in bytes.
+    pub uri_limit: usize,
+    /// The most header field lines accepted.
+    pub max_headers: usize,
+    /// The most bytes buffered for one request head, so a client cannot
+    /// hold memory by never finishing its head.
+    pub max_head_length: usize,
+    /// The longest body accepted, in bytes. A larger Content-Length is
+    /// rejected with the head; a [`Body`](crate::Body) enforces it while
+    /// reading chunked bodies.
+    pub max_body_length: u64,
+}
+
+impl Default for RequestOptions {
+    fn default() -> Self {
+        Self {
+            obs_fold: ObsFold::default(),
+            uri_limit: DEFAULT_MAX_URI_LENGTH,
+            max_headers: 100,
+            max_head_length: 16 * 1024,
+            max_body_length: 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    method: HttpMethodEnum,
+    target: RequestTarget,
+    version: HttpVersion,
+    headers: HeaderMap,
+    framing: BodyFraming,
+}
+
+impl HttpRequest {
+    /// Parses a request head with the default options. Returns the request
+    /// and the number of bytes read up to and including the empty line
+    /// that ends the head; any body or pipelined request follows.
+    pub fn parse(bytes: &[u8]) -> Result<(Self, usize), RequestError> {
+        Self::parse_with(bytes, &RequestOptions::default())
+    }
+
+    /// HTTP-message = start-line CRLF *( field-line CRLF ) CRLF
+    ///
+    /// A bare LF is accepted as a line terminator, RFC 9112 §2.2. Returns
+    /// [`RequestError::Incomplete`] when the buffer ends inside the head;
+    /// use a [`RequestParser`](crate::RequestParser) to resume as more bytes arrive instead.
+    pub fn parse_with(
+        bytes: &[u8],
+        options: &RequestOptions,
+    ) -> Result<(Self, usize), RequestError> {
+        let mut scan = HeadScan::default();
+        match scan.scan(bytes, options.max_head_length)? {
+            Some(end) => Ok((Self::from_lines(bytes, scan.lines(), options)?, end)),
+            None => Err(RequestError::Incomplete),
+        }
+    }
+
+    /// Builds the request from the spans of a complete head: the
+    /// request-line followed by every field line.
+    pub(crate) fn from_lines(
+        bytes: &[u8],
+        lines: &[Location],
+        options: &RequestOptions,
+    ) -> Result<Self, RequestError> {
+        let slice = |line: &Location| &bytes[line.start()..line.end()];
+        let (request_line, field_lines) = match lines.split_first() {
+            Some(lines) => lines,
+            None => return Err(RequestError::Incomplete),
+        };
+        let (method, target, version) =
+            parse_request_line(slice(request_line), request_line.start(), options)?;
+
+        let mut headers = HeaderMap::new();
+        for location in field_lines {
+            let (line, index) = (slice(location), location.start());
+
+            // obs-fold = OWS CRLF RWS
+            if line[0] == b' ' || line[0] == b'\t' {
+                let value = match (options.obs_fold, headers.last_value_mut()) {
+                    (ObsFold::Replace, Some(value)) => value,
+                    _ => return Err(RequestError::ObsFold { offset: index }),
+                };
+                let (start, folded) = trim_ows(line);
+                HeaderValue::parse(folded).map_err(|e| e.offset_by(index + start))?;
+                value.push_fold(folded);
+            } else {
+                if headers.len() == options.max_headers {
+                    return Err(RequestError::TooManyHeaders {
+                        limit: options.max_headers,
+                    });
+                }
+                let (name, value) = parse_field_line(line, index)?;
+                headers.append(name, value);
+            }
+        }
+
+        check_host(&headers, version)?;
+
+        // RFC 9112 §6.1: HTTP/1.0 has no transfer codings, so a body framed
+        // with one cannot be trusted.
+        if !version.supports_chunked() && headers.contains_key(general::TRANSFER_ENCODING) {
+            return Err(RequestError::TransferEncodingInHttp10);
+        }
+
+        let framing = BodyFraming::from_headers(&headers)?;
+        if let BodyFraming::Length(length) = framing {
+            if length > options.max_body_length {
+                return Err(RequestError::ContentTooLarge {
+                    limit: options.max_body_length,
+                });
+            }
+        }
+
+        Ok(HttpRequest {
+            method,
+            target,
+            version,
+            headers,
+            framing,
+        })
+    }
+
+    pub fn method(&self) -> &HttpMethodEnum {
+        &self.method
+    }
+
+    pub fn target(&self) -> &RequestTarget {
+        &self.target
+    }
+
+    pub fn version(&self) -> HttpVersion {
+        self.version
+    }
+
+    /// Whether the connection stays open after this request, RFC 9112
+    /// §9.3. A "close" option always closes it; otherwise HTTP/1.1 keeps
+    /// it open and HTTP/1.0 only when the client sent "keep-alive".
+    pub fn keep_alive(&self) -> bool {
+        let options = self
+            .headers
+            .get_all(general::CONNECTION)
+            .flat_map(|value| value.as_bytes().split(|b| *b == b','))
+            .map(|option| trim_ows(option).1);
+
+        let (mut close, mut keep_alive) = (false, false);
+        for option in options {
+            close |= option.eq_ignore_ascii_case(b"close");
+            keep_alive |= option.eq_ignore_ascii_case(b"keep-alive");
+        }
+        !close && (keep_alive || self.version.keeps_alive_by_default())
+    }
+
+    /// How the body that follows the head is delimited. Read it with a
+    /// [`Body`](crate::Body) over the connection.
+    pub fn framing(&self) -> BodyFraming {
+        self.framing
+    }
+
+    /// Every header field line in the order received.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+/// The character at `offset` in a line, or '\n' past its end.
+fn found_at(line: &[u8], offset: usize) -> char {
+    if offset < line.len() {
+        char_at(line, offset)
+    } else {
+        '\n'
+    }
+}
+
+/// request-line = method SP request-target SP HTTP-version
+fn parse_request_line(
+    line: &[u8],
+    start: usize,
+    options: &RequestOptions,
+) -> Result<(HttpMethodEnum, RequestTarget, HttpVersion), RequestError> {
+    let invalid = |offset: usize| RequestError::InvalidRequestLine {
+        offset: start + offset,
+        found: found_at(line, offset),
+    };
+
+    let method_end = match line.iter().position(|b| *b == b' ') {
+        Some(idx) => idx,
+        None => return Err(invalid(line.len())),
+    };
+    let method = HttpMethodEnum::parse(&line[..method_end]).map_err(|e| e.offset_by(start))?;
+
+    let target_start = method_end + 1;
+    let target_end = match line[target_start..].iter().position(|b| *b == b' ') {
+        Some(0) => return Err(invalid(target_start)),
+        Some(idx) => target_start + idx,
+        None => return Err(invalid(line.len())),
+    };
+    let target =
+        RequestTarget::parse_with_limit(&line[target_start..target_end], options.uri_limit)
+            .map_err(|e| RequestError::InvalidTarget(e.offset_by(start + target_start)))?;
+
+    let version_start = target_end + 1;
+    let version = HttpVersion::parse(&line[version_start..])
+        .map_err(|e| e.offset_by(start + version_start))?;
+
+    Ok((method, target, version))
+}
+
+/// field-line = field-name ":" OWS field-value OWS
+pub(crate) fn parse_field_line(
+    line: &[u8],
+    start: usize,
+) -> Result<(HeaderName, HeaderValue), RequestError> {
+    let colon = match line.iter().position(|b| *b == b':') {
+        Some(idx) => idx,
+        None => {
+            return Err(RequestError::InvalidHeaderName {
+                offset: start + line.len(),
+                found: '\n',
+            })
+        }
+    };
+
+    // Whitespace between the name and the colon is rejected too, RFC 9112
+    // §5.1, since it is not a tchar.
+    let name = &line[..colon];
+    if name.is_empty() {
+        return Err(RequestError::InvalidHeaderName {
+            offset: start,
+            found: ':',
+        });
+    }
+    let name = HeaderName::parse(name).map_err(|e| e.offset_by(start))?;
+
+    let (value_start, value) = trim_ows(&line[colon + 1..]);
+    let value =
+        HeaderValue::parse(value).map_err(|e| e.offset_by(start + colon + 1 + value_start))?;
+
+    Ok((name, value))
+}
+
+/// Strips OWS from both ends, returning the offset of what is left.
+fn trim_ows(value: &[u8]) -> (usize, &[u8]) {
+    let is_ows = |b: &u8| *b == b' ' || *b == b'\t';
+    let start = value.iter().position(|b| !is_ows(b)).unwrap_or(value.len());
+    let end = value
+        .iter()
+        .rposition(|b| !is_ows(b))
+        .map_or(start, |idx| idx + 1);
+    (start, &value[start..end])
+}
+
+/// RFC 9112 §3.2: an HTTP/1.1 request needs exactly one valid Host field.
+/// HTTP/1.0 may leave it out, but no request may carry more than one.
+fn check_host(headers: &HeaderMap, version: HttpVersion) -> Result<(), RequestError> {
+    let mut hosts = headers.get_all(request::HOST);
+
+    let value = match (hosts.next(), hosts.next()) {
+        (Some(_), Some(_)) => return Err(RequestError::DuplicateHost),
+        (Some(value), None) => value.as_bytes(),
+        (None, _) if version.requires_host() => return Err(RequestError::MissingHost),
+        (None, _) => return Ok(()),
+    };
+
+    // Host = uri-host [ ":" port ], empty when the target has no authority.
+    let value = std::str::from_utf8(value).map_err(|_| RequestError::InvalidHostHeader)?;
+    let host_end = value.rfind(']').map_or(0, |idx| idx + 1);
+    let (host, port) = match value[host_end..].rfind(':') {
+        Some(idx) => (&value[..host_end + idx], &value[host_end + idx + 1..]),
+        None => (value, ""),
+    };
+    let valid_port = port.is_empty()
+        || (port.bytes().all(|b| b.is_ascii_digit()) && port.parse::<u16>().is_ok());
+    if (!host.is_empty() && Host::parse(host).is_err()) || !valid_port {
+        return Err(RequestError::InvalidHostHeader);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_notes_frame() {
+    let frame = b"GET /docs/tutorials/linux/shellscripts/howto.html HTTP/1.1\r\n\
+Host: Linode.com\r\n\
+User-Agent: Mozilla/5.0 (Windows; U; Windows NT 6.1; en-US; rv:1.9.1.8) Gecko/20091102 Firefox/3.5.5\r\n\
+Accept: text/html,application/xhtml+xml,\r\n\
+Accept-Language: en-us\r\n\
+Accept-Encoding: gzip,deflate\r\n\
+Accept-Charset: ISO-8859-1,utf-8\r\n\
+Cache-Control: no-cache\r\n\
+\r\n";
+
+    let (request, consumed) = HttpRequest::parse(frame).ok().unwrap();
+    assert_eq!(consumed, frame.len());
+    assert_eq!(request.method(), &HttpMethodEnum::GET);
+    assert_eq!(
+        request.target().path(),
+        Some("/docs/tutorials/linux/shellscripts/howto.html")
+    );
+    assert_eq!(request.version(), HttpVersion::Http11);
+    assert_eq!(request.headers().len(), 7);
+    assert_eq!(request.headers().get("host").unwrap(), "Linode.com");
+    assert_eq!(request.headers().get("CACHE-CONTROL").unwrap(), "no-cache");
+}
+
+#[test]
+fn test_parse_reports_consumed_bytes() {
+    let input = b"\r\nPOST /form HTTP/1.1\nHost: a\n\nname=value";
+
+    let (request, consumed) = HttpRequest::parse(input).ok().unwrap();
+    assert_eq!(request.method(), &HttpMethodEnum::POST);
+    assert_eq!(&input[consumed..], b"name=value");
+}
+
+#[test]
+fn test_parse_trims_field_values() {
+    let input = b"GET / HTTP/1.1\r\nHost:a\r\nX-Empty:\r\nX-Pad: \t padded value \t\r\n\r\n";
+
+    let (request, _) = HttpRequest::parse(input).ok().unwrap();
+    assert_eq!(request.headers().get("x-empty").unwrap(), "");
+    assert_eq!(request.headers().get("x-pad").unwrap(), "padded value");
+}
+
+#[test]
+fn test_parse_obs_fold() {
+    let input = b"GET / HTTP/1.1\r\nHost: a\r\nX-Long: one\r\n  two\r\n\tthree\r\n\r\n";
+
+    assert_eq!(
+        HttpRequest::parse(input).err(),
+        Some(RequestError::ObsFold { offset: 38 })
+    );
+
+    let options = RequestOptions {
+        obs_fold: ObsFold::Replace,
+        ..RequestOptions::default()
+    };
+    let (request, _) = HttpRequest::parse_with(input, &options).ok().unwrap();
+    assert_eq!(request.headers().get("x-long").unwrap(), "one two three");
+}
+
+#[test]
+fn test_parse_request_line_errors() {
+    assert_eq!(
+        HttpRequest::parse(b"GET  / HTTP/1.1\r\n\r\n").err(),
+        Some(RequestError::InvalidRequestLine {
+            offset: 4,
+            found: ' '
+        })
+    );
+    assert_eq!(
+        HttpRequest::parse(b"G@T / HTTP/1.1\r\n\r\n").err(),
+        Some(RequestError::InvalidMethod {
+            offset: 1,
+            found: '@'
+        })
+    );
+    assert_eq!(
+        HttpRequest::parse(b"GET / http/1.1\r\n\r\n").err(),
+        Some(RequestError::InvalidVersion { offset: 6 })
+    );
+    assert_eq!(
+        HttpRequest::parse(b"GET /a%zz HTTP/1.1\r\n\r\n").err(),
+        Some(RequestError::InvalidTarget(
+            crate::UriError::InvalidPercentEscape { offset: 6 }
+        ))
+    );
+    assert_eq!(
+        HttpRequest::parse(b"GET /?a\x7fb HTTP/1.1\r\nHost: a\r\n\r\n").err(),
+        Some(RequestError::InvalidTarget(crate::UriError::InvalidChar {
+            offset: 7,
+            found: '\u{7f}',
+            component: crate::Tag::Query,
+        }))
+    );
+    assert_eq!(
+        HttpRequest::parse(b"GET / HTTP/1.1\r\nHost: a\r\n").err(),
+        Some(RequestError::Incomplete)
+    );
+}
+
+#[test]
+fn test_parse_header_errors() {
+    assert_eq!(
+        HttpRequest::parse(b"GET / HTTP/1.1\r\nHost : a\r\n\r\n").err(),
+        Some(RequestError::InvalidHeaderName {
+            offset: 20,
+            found: ' '
+        })
+    );
+    assert_eq!(
+        HttpRequest::parse(b"GET / HTTP/1.1\r\nHost: a\rb\r\n\r\n").err(),
+        Some(RequestError::InvalidHeaderValue {
+            offset: 23,
+            found: '\r'
+        })
+    );
+    assert_eq!(
+        HttpRequest::parse(b"GET / HTTP/1.1\r\nUser-Agent: x\r\n\r\n").err(),
+        Some(RequestError::MissingHost)
+    );
+    assert_eq!(
+        HttpRequest::parse(b"GET / HTTP/1.1\r\nHost: a\r\nhost: b\r\n\r\n").err(),
+        Some(RequestError::DuplicateHost)
+    );
+    assert_eq!(
+        HttpRequest::parse(b"GET / HTTP/1.1\r\nHost: a b\r\n\r\n").err(),
+        Some(RequestError::InvalidHostHeader)
+    );
+
+    let options = RequestOptions {
+        max_headers: 1,
+        ..RequestOptions::default()
+    };
+    assert_eq!(
+        HttpRequest::parse_with(b"GET / HTTP/1.1\r\nHost: a\r\nX: y\r\n\r\n", &options).err(),
+        Some(RequestError::TooManyHeaders { limit: 1 })
+    );
+}
+
+#[test]
+fn test_parse_long_target_maps_to_414() {
+    let input = format!(
+        "GET /{} HTTP/1.1\r\nHost: a\r\n\r\n",
+        "a".repeat(DEFAULT_MAX_URI_LENGTH)
+    );
+
+    let error = HttpRequest::parse(input.as_bytes()).err().unwrap();
+    assert_eq!(
+        crate::StatusCode::from(&error),
+        crate::StatusCode::UriTooLong
+    );
+}
+
+#[test]
+fn test_parse_http10() {
+    let (request, _) = HttpRequest::parse(b"GET / HTTP/1.0\r\n\r\n").ok().unwrap();
+    assert_eq!(request.version(), HttpVersion::Http10);
+    assert!(!request.keep_alive());
+
+    let input = b"GET / HTTP/1.0\r\nConnection: Keep-Alive\r\n\r\n";
+    let (request, _) = HttpRequest::parse(input).ok().unwrap();
+    assert!(request.keep_alive());
+
+    assert_eq!(
+        HttpRequest::parse(b"POST / HTTP/1.0\r\nTransfer-Encoding: chunked\r\n\r\n").err(),
+        Some(RequestError::TransferEncodingInHttp10)
+    );
+    assert_eq!(
+        HttpRequest::parse(b"GET / HTTP/1.0\r\nHost: a\r\nHost: b\r\n\r\n").err(),
+        Some(RequestError::DuplicateHost)
+    );
+}
+
+#[test]
+fn test_parse_http11_connection() {
+    let (request, _) = HttpRequest::parse(b"GET / HTTP/1.1\r\nHost: a\r\n\r\n")
+        .ok()
+        .unwrap();
+    assert!(request.keep_alive());
+
+    let input = b"GET / HTTP/1.1\r\nHost: a\r\nConnection: upgrade, close\r\n\r\n";
+    let (request, _) = HttpRequest::parse(input).ok().unwrap();
+    assert!(!request.keep_alive());
+}
+
+#[test]
+fn test_parse_unsupported_version() {
+    let error = HttpRequest::parse(b"GET / HTTP/2.0\r\n\r\n").err().unwrap();
+    assert_eq!(
+        error,
+        RequestError::UnsupportedVersion { major: 2, minor: 0 }
+    );
+    assert_eq!(
+        crate::StatusCode::from(&error),
+        crate::StatusCode::HttpVersionNotSupported
+    );
+}
+
+#[test]
+fn test_parse_body_framing() {
+    let input = b"POST /form HTTP/1.1\r\nHost: a\r\nContent-Length: 10\r\n\r\nname=value";
+    let (request, consumed) = HttpRequest::parse(input).ok().unwrap();
+    assert_eq!(request.framing(), BodyFraming::Length(10));
+    assert_eq!(&input[consumed..], b"name=value");
+
+    let input = b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked\r\n\r\n";
+    let (request, _) = HttpRequest::parse(input).ok().unwrap();
+    assert_eq!(request.framing(), BodyFraming::Chunked);
+
+    let input = b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 3\r\nContent-Length: 3\r\n\r\n";
+    assert_eq!(
+        HttpRequest::parse(input).err(),
+        Some(RequestError::DuplicateContentLength)
+    );
+
+    let options = RequestOptions {
+        max_body_length: 9,
+        ..RequestOptions::default()
+    };
+    let input = b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 10\r\n\r\n";
+    let error = HttpRequest::parse_with(input, &options).err().unwrap();
+    assert_eq!(error, RequestError::ContentTooLarge { limit: 9 });
+    assert_eq!(
+        crate::StatusCode::from(&error),
+        crate::StatusCode::ContentTooLarge
+    );
+}
+
+#[test]
+fn test_parse_limit_boundaries() {
+    let input = b"GET / HTTP/1.1\r\nHost: a\r\nX: y\r\n\r\n";
+    let options = RequestOptions {
+        max_headers: 2,
+        max_head_length: input.len(),
+        ..RequestOptions::default()
+    };
+    let (_, consumed) = HttpRequest::parse_with(input, &options).ok().unwrap();
+    assert_eq!(consumed, input.len());
+
+    let options = RequestOptions {
+        max_head_length: input.len() - 1,
+        ..options
+    };
+    assert_eq!(
+        HttpRequest::parse_with(input, &options).err(),
+        Some(RequestError::HeadTooLarge {
+            limit: input.len() - 1
+        })
+    );
+
+    let input = b"GET / HTTP/1.1\r\nHost: a\r\nX: y\r\nZ: z\r\n\r\n";
+    let options = RequestOptions {
+        max_headers: 2,
+        ..RequestOptions::default()
+    };
+    assert_eq!(
+        HttpRequest::parse_with(input, &options).err(),
+        Some(RequestError::TooManyHeaders { limit: 2 })
+    );
+
+    let options = RequestOptions {
+        uri_limit: 8,
+        ..RequestOptions::default()
+    };
+    let input = b"GET /aaaaaaa HTTP/1.1\r\nHost: a\r\n\r\n";
+    assert!(HttpRequest::parse_with(input, &options).is_ok());
+    let input = b"GET /aaaaaaaa HTTP/1.1\r\nHost: a\r\n\r\n";
+    let error = HttpRequest::parse_with(input, &options).err().unwrap();
+    assert_eq!(
+        crate::StatusCode::from(&error),
+        crate::StatusCode::UriTooLong
+    );
+}
+
+#[test]
+fn test_parse_incomplete_head() {
+    assert_eq!(
+        HttpRequest::parse(b"").err(),
+        Some(RequestError::Incomplete)
+    );
+    assert_eq!(
+        HttpRequest::parse(b"\r\n\r\n").err(),
+        Some(RequestError::Incomplete)
+    );
+    assert_eq!(
+        HttpRequest::parse(b"GET / HTTP/1.1\r\nHost: a\r\n").err(),
+        Some(RequestError::Incomplete)
+    );
+}
+
+#[test]
+fn test_parse_host_port_boundaries() {
+    for host in ["a:65535", "a:", "a:0", "[::1]:80", ""] {
+        let input = format!("GET / HTTP/1.1\r\nHost: {}\r\n\r\n", host);
+        assert!(HttpRequest::parse(input.as_bytes()).is_ok(), "{}", host);
+    }
+    for host in ["a:65536", "a:+80", "a:8x", "[::1]x"] {
+        let input = format!("GET / HTTP/1.1\r\nHost: {}\r\n\r\n", host);
+        assert_eq!(
+            HttpRequest::parse(input.as_bytes()).err(),
+            Some(RequestError::InvalidHostHeader),
+            "{}",
+            host
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt::Display;
+use std::io::{self, Write};
+
+use crate::{HeaderMap, HttpVersion, RequestError, StatusCode, UriError};
+
+/// A response without a body, enough for the server's error paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    status: StatusCode,
+    version: HttpVersion,
+    keep_alive: bool,
+    headers: HeaderMap,
+}
+
+impl HttpResponse {
+    /// An HTTP/1.1 response that keeps the connection open.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            version: HttpVersion::Http11,
+            keep_alive: true,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Answers with the version of the request, so an HTTP/1.0 client is
+    /// not sent anything it cannot read.
+    pub fn with_version(mut self, version: HttpVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn with_keep_alive(mut self, keep_alive: bool) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn version(&self) -> HttpVersion {
+        self.version
+    }
+
+    pub fn keep_alive(&self) -> bool {
+        self.keep_alive
+    }
+
+    /// The fields written after the status line. Connection and
+    /// Content-Length are written from the response itself.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    /// Writes the response to the connection. Field values go out as the
+    /// bytes they hold, so obs-text is sent unchanged.
+    pub fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "{} {}\r\n", self.version, self.status)?;
+        self.headers.write_to(out)?;
+        if let Some(connection) = self.connection() {
+            write!(out, "Connection: {}\r\n", connection)?;
+        }
+        out.write_all(b"Content-Length: 0\r\n\r\n")
+    }
+
+    /// A Connection field is only written when the version's default does
+    /// not already say what happens to the connection.
+    fn connection(&self) -> Option<&'static str> {
+        match (self.keep_alive, self.version.keeps_alive_by_default()) {
+            (true, false) => Some("keep-alive"),
+            (false, true) => Some("close"),
+            _ => None,
+        }
+    }
+}
+
+impl From<&UriError> for HttpResponse {
+    fn from(error: &UriError) -> Self {
+        HttpResponse::new(StatusCode::from(error))
+    }
+}
+
+/// The connection is closed after a malformed request, since where the
+/// next one starts cannot be trusted.
+impl From<&RequestError> for HttpResponse {
+    fn from(error: &RequestError) -> Self {
+        HttpResponse::new(StatusCode::from(error)).with_keep_alive(false)
+    }
+}
+
+/// Serializes the response as text, with obs-text in field values
+/// replaced; [`HttpResponse::write_to`] writes the bytes for the wire.
+///
+/// status-line = HTTP-version SP status-code SP [ reason-phrase ]
+impl Display for HttpResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}\r\n", self.version, self.status)?;
+        write!(f, "{}", self.headers)?;
+        if let Some(connection) = self.connection() {
+            write!(f, "Connection: {}\r\n", connection)?;
+        }
+        write!(f, "Content-Length: 0\r\n\r\n")
+    }
+}
+
+#[test]
+fn test_response_for_long_uri() {
+    let target = format!("http://telemakos.io/{}", "a".repeat(8000));
+    let mut tokenizer = crate::Tokenizer::new(target);
+    let error = crate::Uri::parse_tokens(&mut tokenizer).err().unwrap();
+
+    let response = HttpResponse::from(&error);
+    assert_eq!(response.status(), StatusCode::UriTooLong);
+    assert_eq!(
+        response.to_string(),
+        "HTTP/1.1 414 URI Too Long\r\nContent-Length: 0\r\n\r\n"
+    );
+}
+
+#[test]
+fn test_response_connection() {
+    let response = HttpResponse::new(StatusCode::Ok).with_version(HttpVersion::Http10);
+    assert_eq!(
+        response.to_string(),
+        "HTTP/1.0 200 OK\r\nConnection: keep-alive\r\nContent-Length: 0\r\n\r\n"
+    );
+
+    let response = response.with_keep_alive(false);
+    assert_eq!(
+        response.to_string(),
+        "HTTP/1.0 200 OK\r\nContent-Length: 0\r\n\r\n"
+    );
+
+    let response = HttpResponse::from(&RequestError::UnsupportedVersion { major: 2, minor: 0 });
+    assert_eq!(
+        response.to_string(),
+        "HTTP/1.1 505 HTTP Version Not Supported\r\nConnection: close\r\nContent-Length: 0\r\n\r\n"
+    );
+}
+
+#[test]
+fn test_response_headers() {
+    use crate::header::{general, response};
+
+    let mut response = HttpResponse::new(StatusCode::Ok);
+    response
+        .headers_mut()
+        .append(response::SERVER, "rust_http_server".parse().ok().unwrap());
+    response
+        .headers_mut()
+        .append(general::VIA, "1.0 lb".parse().ok().unwrap());
+
+    assert_eq!(
+        response.to_string(),
+        "HTTP/1.1 200 OK\r\nserver: rust_http_server\r\nvia: 1.0 lb\r\nContent-Length: 0\r\n\r\n"
+    );
+}
+
+#[test]
+fn test_response_write_to_keeps_obs_text() {
+    use crate::header::response;
+
+    let mut response = HttpResponse::new(StatusCode::Ok).with_keep_alive(false);
+    response.headers_mut().append(
+        response::SERVER,
+        crate::HeaderValue::parse(b"caf\xe9").ok().unwrap(),
+    );
+
+    let mut out = Vec::new();
+    response.write_to(&mut out).ok().unwrap();
+    assert_eq!(
+        out,
+        b"HTTP/1.1 200 OK\r\nserver: caf\xe9\r\nConnection: close\r\nContent-Length: 0\r\n\r\n"
+    );
+    assert_eq!(
+        response.to_string(),
+        "HTTP/1.1 200 OK\r\nserver: caf\u{FFFD}\r\nConnection: close\r\nContent-Length: 0\r\n\r\n"
+    );
+}
@@ -0,0 +1,122 @@
+use std::fmt::Display;
+
+use crate::{RequestError, UriError};
+
+/// The status codes the server sends, RFC 9110 §15.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusCode {
+    Ok,
+    BadRequest,
+    NotFound,
+    ContentTooLarge,
+    UriTooLong,
+    RequestHeaderFieldsTooLarge,
+    InternalServerError,
+    NotImplemented,
+    HttpVersionNotSupported,
+}
+
+impl StatusCode {
+    pub fn as_u16(&self) -> u16 {
+        match self {
+            StatusCode::Ok => 200,
+            StatusCode::BadRequest => 400,
+            StatusCode::NotFound => 404,
+            StatusCode::ContentTooLarge => 413,
+            StatusCode::UriTooLong => 414,
+            StatusCode::RequestHeaderFieldsTooLarge => 431,
+            StatusCode::InternalServerError => 500,
+            StatusCode::NotImplemented => 501,
+            StatusCode::HttpVersionNotSupported => 505,
+        }
+    }
+
+    /// The reason phrase suggested by RFC 9110 §15.
+    pub fn reason_phrase(&self) -> &'static str {
+        match self {
+            StatusCode::Ok => "OK",
+            StatusCode::BadRequest => "Bad Request",
+            StatusCode::NotFound => "Not Found",
+            StatusCode::ContentTooLarge => "Content Too Large",
+            StatusCode::UriTooLong => "URI Too Long",
+            StatusCode::RequestHeaderFieldsTooLarge => "Request Header Fields Too Large",
+            StatusCode::InternalServerError => "Internal Server Error",
+            StatusCode::NotImplemented => "Not Implemented",
+            StatusCode::HttpVersionNotSupported => "HTTP Version Not Supported",
+        }
+    }
+
+    pub fn is_client_error(&self) -> bool {
+        (400..500).contains(&self.as_u16())
+    }
+}
+
+/// Formats as the status-code and reason-phrase of a status line,
+/// `414 URI Too Long`.
+impl Display for StatusCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.as_u16(), self.reason_phrase())
+    }
+}
+
+/// The response for a request-target that failed to parse. An oversized
+/// target gets 414, RFC 9112 §3; everything else is a malformed request.
+impl From<&UriError> for StatusCode {
+    fn from(error: &UriError) -> Self {
+        match error {
+            UriError::UriTooLong { .. } => StatusCode::UriTooLong,
+            _ => StatusCode::BadRequest,
+        }
+    }
+}
+
+/// The response for a request that failed to parse. Too many header
+/// fields or too long a head get 431, RFC 6585 §5, too long a body 413, a
+/// transfer coding other than chunked 501, RFC 9112 §6.1, and a major
+/// version other than 1 gets 505.
+impl From<&RequestError> for StatusCode {
+    fn from(error: &RequestError) -> Self {
+        match error {
+            RequestError::InvalidTarget(e) => StatusCode::from(e),
+            RequestError::UnsupportedVersion { .. } => StatusCode::HttpVersionNotSupported,
+            RequestError::ContentTooLarge { .. } => StatusCode::ContentTooLarge,
+            RequestError::UnsupportedTransferCoding => StatusCode::NotImplemented,
+            RequestError::TooManyHeaders { .. } | RequestError::HeadTooLarge { .. } => {
+                StatusCode::RequestHeaderFieldsTooLarge
+            }
+            _ => StatusCode::BadRequest,
+        }
+    }
+}
+
+#[test]
+fn test_status_display() {
+    assert_eq!(StatusCode::UriTooLong.to_string(), "414 URI Too Long");
+    assert!(StatusCode::UriTooLong.is_client_error());
+    assert!(!StatusCode::InternalServerError.is_client_error());
+}
+
+#[test]
+fn test_status_from_uri_error() {
+    let too_long = UriError::UriTooLong {
+        length: 8001,
+        limit: 8000,
+    };
+    assert_eq!(StatusCode::from(&too_long), StatusCode::UriTooLong);
+    assert_eq!(
+        StatusCode::from(&UriError::EmptyHost),
+        StatusCode::BadRequest
+    );
+}
+
+#[test]
+fn test_status_from_request_error() {
+    assert_eq!(
+        StatusCode::from(&RequestError::TooManyHeaders { limit: 100 }),
+        StatusCode::RequestHeaderFieldsTooLarge
+    );
+    assert_eq!(
+        StatusCode::from(&RequestError::MissingHost),
+        StatusCode::BadRequest
+    );
+}
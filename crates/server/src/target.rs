@@ -0,0 +1,355 @@
+use std::fmt::Display;
+
+use crate::{
+    char_at, has_scheme, is_pchar, is_pct_encoded, Host, Tag, Uri, UriError, UriRef,
+    DEFAULT_MAX_URI_LENGTH,
+};
+
+/// The request-target of a request line, RFC 9112 §3.2.
+///
+/// request-target = origin-form / absolute-form / authority-form / asterisk-form
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestTarget {
+    /// `/path?query`, the usual form sent to an origin server. Both parts
+    /// are kept as written, still percent-encoded.
+    Origin { path: String, query: Option<String> },
+    /// `http://host/path?query`, sent to proxies.
+    Absolute(Uri),
+    /// `host:port`, only used with CONNECT.
+    Authority { host: Host, port: u16 },
+    /// `*`, only used with a server-wide OPTIONS request.
+    Asterisk,
+}
+
+impl RequestTarget {
+    /// Parses a request-target. Targets longer than
+    /// [`DEFAULT_MAX_URI_LENGTH`] are rejected.
+    pub fn parse(input: &[u8]) -> Result<Self, UriError> {
+        Self::parse_with_limit(input, DEFAULT_MAX_URI_LENGTH)
+    }
+
+    /// Parses with a custom length limit in bytes.
+    ///
+    /// The form is picked from the leading bytes: `*`, a '/', a scheme
+    /// followed by "://", or otherwise `host:port`. Fragments are never
+    /// part of a request-target.
+    pub fn parse_with_limit(input: &[u8], limit: usize) -> Result<Self, UriError> {
+        if input.len() > limit {
+            return Err(UriError::UriTooLong {
+                length: input.len(),
+                limit,
+            });
+        }
+
+        match input.first() {
+            None => Err(UriError::UnexpectedEnd { offset: 0 }),
+            Some(b'*') if input.len() == 1 => Ok(RequestTarget::Asterisk),
+            Some(b'/') => Self::parse_origin_form(input),
+            Some(_) if has_scheme(input) && has_authority(input) => {
+                let parts = UriRef::parse_with_limit(input, limit)?;
+                reject_fragment(&parts)?;
+                Ok(RequestTarget::Absolute(parts.to_uri()?))
+            }
+            Some(_) => Self::parse_authority_form(input),
+        }
+    }
+
+    /// origin-form = absolute-path [ "?" query ]
+    ///
+    /// absolute-path = 1*( "/" segment )
+    ///
+    /// Segments may be empty, so `//images/a.png` is a path here and not
+    /// the network-path reference it would be in a URI.
+    fn parse_origin_form(input: &[u8]) -> Result<Self, UriError> {
+        let (path, query) = match input.iter().position(|b| *b == b'?') {
+            Some(idx) => (&input[..idx], Some(idx + 1)),
+            None => (input, None),
+        };
+        check_origin_chars(input, 0, path.len(), Tag::Path)?;
+        if let Some(start) = query {
+            check_origin_chars(input, start, input.len(), Tag::Query)?;
+        }
+
+        // Every byte was checked to be ASCII.
+        let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+        Ok(RequestTarget::Origin {
+            path: text(path),
+            query: query.map(|start| text(&input[start..])),
+        })
+    }
+
+    /// authority-form = uri-host ":" port
+    ///
+    /// CONNECT names the host of a tunnel, RFC 9110 §9.3.6, so a registered
+    /// name is held to the letters, digits, '-', '.' and '_' of host names
+    /// rather than the wider reg-name grammar.
+    fn parse_authority_form(input: &[u8]) -> Result<Self, UriError> {
+        let input = std::str::from_utf8(input).map_err(|e| UriError::InvalidHostChar {
+            offset: e.valid_up_to(),
+            found: char::REPLACEMENT_CHARACTER,
+        })?;
+
+        // The port separator is the last ':' outside an IP-literal.
+        let host_end = match input.rfind(']') {
+            Some(idx) => idx + 1,
+            None => 0,
+        };
+        let (host, port) = match input[host_end..].rfind(':') {
+            Some(idx) => input.split_at(host_end + idx),
+            None => {
+                return Err(UriError::UnexpectedEnd {
+                    offset: input.len(),
+                })
+            }
+        };
+        let port_offset = host.len() + 1;
+        let port = &port[1..];
+
+        if host.is_empty() {
+            return Err(UriError::EmptyHost);
+        }
+        if !host.starts_with('[') {
+            let invalid = host.char_indices().find(|(_, c)| {
+                c.is_ascii() && !(c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_'))
+            });
+            if let Some((offset, found)) = invalid {
+                return Err(UriError::InvalidHostChar { offset, found });
+            }
+        }
+        let host = Host::parse(host)?;
+
+        if let Some((offset, found)) = port.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+            return Err(UriError::InvalidChar {
+                offset: port_offset + offset,
+                found,
+                component: Tag::Port,
+            });
+        }
+        let port = match port.parse::<u16>() {
+            Ok(port) => port,
+            Err(_) if port.is_empty() => {
+                return Err(UriError::UnexpectedEnd {
+                    offset: input.len(),
+                })
+            }
+            Err(_) => {
+                return Err(UriError::PortOverflow {
+                    offset: port_offset,
+                })
+            }
+        };
+
+        Ok(RequestTarget::Authority { host, port })
+    }
+
+    /// The path of origin-form and absolute-form targets.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            RequestTarget::Origin { path, .. } => Some(path),
+            RequestTarget::Absolute(uri) => Some(uri.path()),
+            _ => None,
+        }
+    }
+
+    /// The raw query of origin-form and absolute-form targets.
+    pub fn query(&self) -> Option<&str> {
+        match self {
+            RequestTarget::Origin { query, .. } => query.as_deref(),
+            RequestTarget::Absolute(uri) => uri.query(),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the scheme is followed by "//", telling `http://h` apart from
+/// the authority-form `h:80`.
+fn has_authority(input: &[u8]) -> bool {
+    match input.iter().position(|b| *b == b':') {
+        Some(idx) => input[idx + 1..].starts_with(b"//"),
+        None => false,
+    }
+}
+
+/// Checks `input[start..end]` as a path, `*( pchar / "/" )`, or as a query,
+/// which also allows '?'.
+fn check_origin_chars(
+    input: &[u8],
+    start: usize,
+    end: usize,
+    component: Tag,
+) -> Result<(), UriError> {
+    let mut i = start;
+    while i < end {
+        match input[i] {
+            b'%' if is_pct_encoded(&input[..end], i) => i += 3,
+            b'%' => return Err(UriError::InvalidPercentEscape { offset: i }),
+            b'/' => i += 1,
+            b'?' if component == Tag::Query => i += 1,
+            b if b.is_ascii() && is_pchar(b as char) => i += 1,
+            b'#' => {
+                return Err(UriError::InvalidChar {
+                    offset: i,
+                    found: '#',
+                    component: Tag::Fragment,
+                })
+            }
+            _ => {
+                return Err(UriError::InvalidChar {
+                    offset: i,
+                    found: char_at(input, i),
+                    component,
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+fn reject_fragment(parts: &UriRef<'_>) -> Result<(), UriError> {
+    match parts.location(Tag::Fragment) {
+        Some(location) => Err(UriError::InvalidChar {
+            offset: location.start() - 1,
+            found: '#',
+            component: Tag::Fragment,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Writes the target as it appears in a request line.
+impl Display for RequestTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestTarget::Origin { path, query } => {
+                write!(f, "{}", path)?;
+                if let Some(query) = query {
+                    write!(f, "?{}", query)?;
+                }
+                Ok(())
+            }
+            RequestTarget::Absolute(uri) => write!(f, "{}", uri),
+            RequestTarget::Authority { host, port } => write!(f, "{}:{}", host, port),
+            RequestTarget::Asterisk => write!(f, "*"),
+        }
+    }
+}
+
+#[test]
+fn test_origin_form() {
+    let target = RequestTarget::parse(b"/where?q=now").ok().unwrap();
+
+    assert_eq!(target.path(), Some("/where"));
+    assert_eq!(target.query(), Some("q=now"));
+    assert_eq!(target.to_string(), "/where?q=now");
+    assert_eq!(
+        RequestTarget::parse(b"/?a\x01b").err(),
+        Some(UriError::InvalidChar {
+            offset: 3,
+            found: '\u{1}',
+            component: Tag::Query,
+        })
+    );
+    assert_eq!(
+        RequestTarget::parse(b"/caf%E9").ok().unwrap().path(),
+        Some("/caf%E9")
+    );
+    assert_eq!(
+        RequestTarget::parse(b"//x?y").ok(),
+        Some(RequestTarget::Origin {
+            path: String::from("//x"),
+            query: Some(String::from("y")),
+        })
+    );
+    assert_eq!(
+        RequestTarget::parse(b"/a?b#c").err(),
+        Some(UriError::InvalidChar {
+            offset: 4,
+            found: '#',
+            component: Tag::Fragment,
+        })
+    );
+    assert_eq!(
+        RequestTarget::parse(b"/a%2?").err(),
+        Some(UriError::InvalidPercentEscape { offset: 2 })
+    );
+    assert_eq!(
+        RequestTarget::parse(b"/a#frag").err(),
+        Some(UriError::InvalidChar {
+            offset: 2,
+            found: '#',
+            component: Tag::Fragment,
+        })
+    );
+}
+
+#[test]
+fn test_absolute_form() {
+    let target = RequestTarget::parse(b"http://www.example.org/pub/WWW/TheProject.html")
+        .ok()
+        .unwrap();
+
+    match &target {
+        RequestTarget::Absolute(uri) => assert_eq!(uri.host(), "www.example.org"),
+        _ => panic!("expected absolute-form, got {:?}", target),
+    }
+    assert_eq!(target.path(), Some("/pub/WWW/TheProject.html"));
+}
+
+#[test]
+fn test_authority_form() {
+    let target = RequestTarget::parse(b"www.example.com:80").ok().unwrap();
+    assert_eq!(
+        target,
+        RequestTarget::Authority {
+            host: Host::RegName(String::from("www.example.com")),
+            port: 80,
+        }
+    );
+
+    let target = RequestTarget::parse(b"[::1]:8443").ok().unwrap();
+    assert_eq!(target.to_string(), "[::1]:8443");
+    assert_eq!(target.path(), None);
+}
+
+#[test]
+fn test_authority_form_errors() {
+    assert_eq!(
+        RequestTarget::parse(b"example.com").err(),
+        Some(UriError::UnexpectedEnd { offset: 11 })
+    );
+    assert_eq!(
+        RequestTarget::parse(b"example.com:").err(),
+        Some(UriError::UnexpectedEnd { offset: 12 })
+    );
+    assert_eq!(
+        RequestTarget::parse(b":443").err(),
+        Some(UriError::EmptyHost)
+    );
+    assert_eq!(
+        RequestTarget::parse(b"example.com:99999").err(),
+        Some(UriError::PortOverflow { offset: 12 })
+    );
+    assert_eq!(
+        RequestTarget::parse(b"*x:80").err(),
+        Some(UriError::InvalidHostChar {
+            offset: 0,
+            found: '*'
+        })
+    );
+    assert_eq!(
+        RequestTarget::parse(b"a%41:80").err(),
+        Some(UriError::InvalidHostChar {
+            offset: 1,
+            found: '%'
+        })
+    );
+}
+
+#[test]
+fn test_asterisk_form() {
+    assert_eq!(
+        RequestTarget::parse(b"*").ok(),
+        Some(RequestTarget::Asterisk)
+    );
+    assert!(RequestTarget::parse(b"*/a").is_err());
+}
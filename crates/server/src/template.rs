@@ -0,0 +1,629 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::{
+    is_pct_encoded, is_sub_delim, is_unreserved, percent_encode, TemplateError, Tokenizer, Uri,
+};
+
+/// A value bound to a template variable, RFC 6570 §2.3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateValue {
+    String(String),
+    List(Vec<String>),
+    /// An associative array; expansion keeps the insertion order.
+    Map(Vec<(String, String)>),
+}
+
+impl TemplateValue {
+    /// Empty lists and maps count as undefined and expand to nothing.
+    fn is_defined(&self) -> bool {
+        match self {
+            TemplateValue::String(_) => true,
+            TemplateValue::List(items) => !items.is_empty(),
+            TemplateValue::Map(pairs) => !pairs.is_empty(),
+        }
+    }
+}
+
+impl From<&str> for TemplateValue {
+    fn from(value: &str) -> Self {
+        TemplateValue::String(String::from(value))
+    }
+}
+
+impl From<String> for TemplateValue {
+    fn from(value: String) -> Self {
+        TemplateValue::String(value)
+    }
+}
+
+impl From<Vec<&str>> for TemplateValue {
+    fn from(items: Vec<&str>) -> Self {
+        TemplateValue::List(items.into_iter().map(String::from).collect())
+    }
+}
+
+impl From<Vec<(&str, &str)>> for TemplateValue {
+    fn from(pairs: Vec<(&str, &str)>) -> Self {
+        TemplateValue::Map(
+            pairs
+                .into_iter()
+                .map(|(k, v)| (String::from(k), String::from(v)))
+                .collect(),
+        )
+    }
+}
+
+/// The variables a template is expanded with, by name.
+pub type TemplateVars = HashMap<String, TemplateValue>;
+
+/// operator = op-level2 / op-level3
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Simple,
+    Reserved,
+    Fragment,
+    Label,
+    PathSegment,
+    PathParameter,
+    Query,
+    QueryContinuation,
+}
+
+/// The expansion behaviour of each operator, RFC 6570 Appendix A.
+struct Behaviour {
+    first: &'static str,
+    sep: &'static str,
+    named: bool,
+    ifemp: &'static str,
+    allow_reserved: bool,
+}
+
+impl Operator {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '+' => Some(Operator::Reserved),
+            '#' => Some(Operator::Fragment),
+            '.' => Some(Operator::Label),
+            '/' => Some(Operator::PathSegment),
+            ';' => Some(Operator::PathParameter),
+            '?' => Some(Operator::Query),
+            '&' => Some(Operator::QueryContinuation),
+            _ => None,
+        }
+    }
+
+    fn behaviour(&self) -> Behaviour {
+        let (first, sep, named, ifemp, allow_reserved) = match self {
+            Operator::Simple => ("", ",", false, "", false),
+            Operator::Reserved => ("", ",", false, "", true),
+            Operator::Fragment => ("#", ",", false, "", true),
+            Operator::Label => (".", ".", false, "", false),
+            Operator::PathSegment => ("/", "/", false, "", false),
+            Operator::PathParameter => (";", ";", true, "", false),
+            Operator::Query => ("?", "&", true, "=", false),
+            Operator::QueryContinuation => ("&", "&", true, "=", false),
+        };
+        Behaviour {
+            first,
+            sep,
+            named,
+            ifemp,
+            allow_reserved,
+        }
+    }
+}
+
+/// modifier-level4 = prefix / explode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Modifier {
+    None,
+    /// prefix = ":" max-length
+    Prefix(usize),
+    /// explode = "*"
+    Explode,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VarSpec {
+    name: String,
+    modifier: Modifier,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Expression {
+        operator: Operator,
+        variables: Vec<VarSpec>,
+    },
+}
+
+/// A URI Template, RFC 6570, with all four levels of expressions.
+///
+/// ```
+/// use server::{TemplateVars, UriTemplate};
+///
+/// let template = UriTemplate::parse("/users/{id}{?fields*}").unwrap();
+///
+/// let mut vars = TemplateVars::new();
+/// vars.insert("id".into(), "42".into());
+/// vars.insert("fields".into(), vec!["name", "email"].into());
+/// assert_eq!(template.expand_to_string(&vars), "/users/42?fields=name&fields=email");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UriTemplate {
+    source: String,
+    parts: Vec<Part>,
+}
+
+impl UriTemplate {
+    /// URI-Template = *( literals / expression )
+    pub fn parse(template: &str) -> Result<Self, TemplateError> {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let bytes = template.as_bytes();
+        let mut chars = template.char_indices();
+
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '{' => {
+                    let close = match template[offset..].find('}') {
+                        Some(idx) => offset + idx,
+                        None => return Err(TemplateError::UnclosedExpression { offset }),
+                    };
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(parse_expression(&template[offset + 1..close], offset + 1)?);
+                    // Skip the expression body and its closing brace.
+                    for _ in template[offset + 1..=close].chars() {
+                        chars.next();
+                    }
+                }
+                '%' if is_pct_encoded(bytes, offset) => literal.push(c),
+                // literals excludes CTL, SP, DQUOTE, "'", "%" (aside from
+                // pct-encoded), "<", ">", "\", "^", "`", "{", "|", "}"
+                '}' | '"' | '\'' | '%' | '<' | '>' | '\\' | '^' | '`' | '|' => {
+                    return Err(TemplateError::InvalidLiteral { offset, found: c })
+                }
+                c if c.is_control() || c == ' ' => {
+                    return Err(TemplateError::InvalidLiteral { offset, found: c })
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        Ok(Self {
+            source: String::from(template),
+            parts,
+        })
+    }
+
+    /// The names of every variable in the template, in order of appearance.
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.parts
+            .iter()
+            .flat_map(|part| match part {
+                Part::Literal(_) => [].iter(),
+                Part::Expression { variables, .. } => variables.iter(),
+            })
+            .map(|var| var.name.as_str())
+    }
+
+    /// Expands the template into a string. Variables missing from `vars`
+    /// are undefined and expand to nothing.
+    pub fn expand_to_string(&self, vars: &TemplateVars) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => out.push_str(&encode(literal, true)),
+                Part::Expression {
+                    operator,
+                    variables,
+                } => expand_expression(&mut out, *operator, variables, vars),
+            }
+        }
+        out
+    }
+
+    /// Expands a template for an absolute URI, such as
+    /// `https://telemakos.io/users/{id}`.
+    pub fn expand(&self, vars: &TemplateVars) -> Result<Uri, TemplateError> {
+        let mut tokenizer = Tokenizer::new(self.expand_to_string(vars));
+        Ok(Uri::parse_tokens(&mut tokenizer)?)
+    }
+
+    /// Expands a template for a relative reference, such as a route
+    /// `/users/{id}`, and resolves it against `base`.
+    pub fn expand_against(&self, base: &Uri, vars: &TemplateVars) -> Result<Uri, TemplateError> {
+        Ok(base.join(&self.expand_to_string(vars))?)
+    }
+}
+
+/// expression = "{" [ operator ] variable-list "}"
+fn parse_expression(body: &str, start: usize) -> Result<Part, TemplateError> {
+    let first = body.chars().next();
+    let (operator, list, list_start) = match (first, first.and_then(Operator::from_char)) {
+        (_, Some(operator)) => (operator, &body[1..], start + 1),
+        // op-reserve, set aside for future extensions
+        (Some(c @ ('=' | ',' | '!' | '@' | '|')), None) => {
+            return Err(TemplateError::InvalidOperator {
+                offset: start,
+                found: c,
+            })
+        }
+        _ => (Operator::Simple, body, start),
+    };
+
+    // variable-list = varspec *( "," varspec )
+    let mut variables = vec![];
+    let mut offset = list_start;
+    for spec in list.split(',') {
+        variables.push(parse_varspec(spec, offset)?);
+        offset += spec.len() + 1;
+    }
+
+    Ok(Part::Expression {
+        operator,
+        variables,
+    })
+}
+
+/// varspec = varname [ modifier-level4 ]
+fn parse_varspec(spec: &str, start: usize) -> Result<VarSpec, TemplateError> {
+    let (name, modifier) = if let Some(name) = spec.strip_suffix('*') {
+        (name, Modifier::Explode)
+    } else if let Some((name, length)) = spec.split_once(':') {
+        // max-length = %x31-39 0*3DIGIT, a number from 1 to 9999
+        let offset = start + name.len() + 1;
+        let valid = !length.is_empty()
+            && length.len() <= 4
+            && !length.starts_with('0')
+            && length.bytes().all(|b| b.is_ascii_digit());
+        match length.parse::<usize>() {
+            Ok(max) if valid => (name, Modifier::Prefix(max)),
+            _ => return Err(TemplateError::InvalidPrefix { offset }),
+        }
+    } else {
+        (spec, Modifier::None)
+    };
+
+    // varname = varchar *( ["."] varchar )
+    // varchar = ALPHA / DIGIT / "_" / pct-encoded
+    let bytes = name.as_bytes();
+    if name.is_empty() {
+        return Err(TemplateError::InvalidVarname {
+            offset: start,
+            found: spec.chars().next().unwrap_or('}'),
+        });
+    }
+    let mut previous = '.';
+    for (i, c) in name.char_indices() {
+        let valid = match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '_' => true,
+            '%' => is_pct_encoded(bytes, i),
+            // Hex digits of an escape were accepted with their '%'.
+            _ if i >= 1 && bytes[i - 1] == b'%' || i >= 2 && bytes[i - 2] == b'%' => {
+                c.is_ascii_hexdigit()
+            }
+            '.' => previous != '.' && i + 1 < name.len(),
+            _ => false,
+        };
+        if !valid {
+            return Err(TemplateError::InvalidVarname {
+                offset: start + i,
+                found: c,
+            });
+        }
+        previous = c;
+    }
+
+    Ok(VarSpec {
+        name: String::from(name),
+        modifier,
+    })
+}
+
+/// Expands one expression, RFC 6570 Appendix A.
+fn expand_expression(out: &mut String, operator: Operator, specs: &[VarSpec], vars: &TemplateVars) {
+    let behaviour = operator.behaviour();
+    let mut first = true;
+
+    for spec in specs {
+        let value = match vars.get(&spec.name) {
+            Some(value) if value.is_defined() => value,
+            _ => continue,
+        };
+        out.push_str(if first {
+            behaviour.first
+        } else {
+            behaviour.sep
+        });
+        first = false;
+
+        let encode = |s: &str| encode(s, behaviour.allow_reserved);
+        let named = |out: &mut String, name: &str, value: &str| {
+            out.push_str(name);
+            if value.is_empty() {
+                out.push_str(behaviour.ifemp);
+            } else {
+                out.push('=');
+                out.push_str(value);
+            }
+        };
+
+        match (value, spec.modifier) {
+            (TemplateValue::String(value), modifier) => {
+                let value = match modifier {
+                    Modifier::Prefix(max) => match value.char_indices().nth(max) {
+                        Some((end, _)) => &value[..end],
+                        None => value.as_str(),
+                    },
+                    _ => value.as_str(),
+                };
+                if behaviour.named {
+                    named(out, &spec.name, &encode(value));
+                } else {
+                    out.push_str(&encode(value));
+                }
+            }
+            (TemplateValue::List(items), Modifier::Explode) => {
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|item| {
+                        let mut part = String::new();
+                        if behaviour.named {
+                            named(&mut part, &spec.name, &encode(item));
+                        } else {
+                            part.push_str(&encode(item));
+                        }
+                        part
+                    })
+                    .collect();
+                out.push_str(&items.join(behaviour.sep));
+            }
+            (TemplateValue::Map(pairs), Modifier::Explode) => {
+                let pairs: Vec<String> = pairs
+                    .iter()
+                    .map(|(k, v)| {
+                        let mut part = String::new();
+                        if behaviour.named {
+                            named(&mut part, &encode(k), &encode(v));
+                        } else {
+                            part.push_str(&format!("{}={}", encode(k), encode(v)));
+                        }
+                        part
+                    })
+                    .collect();
+                out.push_str(&pairs.join(behaviour.sep));
+            }
+            // Prefixes do not apply to composite values, RFC 6570 §2.4.1.
+            (TemplateValue::List(items), _) => {
+                let joined: Vec<String> = items.iter().map(|item| encode(item)).collect();
+                if behaviour.named {
+                    named(out, &spec.name, &joined.join(","));
+                } else {
+                    out.push_str(&joined.join(","));
+                }
+            }
+            (TemplateValue::Map(pairs), _) => {
+                let joined: Vec<String> = pairs
+                    .iter()
+                    .flat_map(|(k, v)| [encode(k), encode(v)])
+                    .collect();
+                if behaviour.named {
+                    named(out, &spec.name, &joined.join(","));
+                } else {
+                    out.push_str(&joined.join(","));
+                }
+            }
+        }
+    }
+}
+
+/// Percent-encodes everything outside the unreserved set, or outside
+/// unreserved and reserved when `allow_reserved` is set. Existing escapes
+/// are kept in the second case.
+fn encode(input: &str, allow_reserved: bool) -> String {
+    if !allow_reserved {
+        return percent_encode(input, is_unreserved);
+    }
+
+    let bytes = input.as_bytes();
+    let mut out = String::with_capacity(input.len());
+    for (i, c) in input.char_indices() {
+        if c == '%' && is_pct_encoded(bytes, i) {
+            out.push(c);
+        } else {
+            out.push_str(&percent_encode(
+                c.encode_utf8(&mut [0; 4]),
+                is_reserved_or_unreserved,
+            ));
+        }
+    }
+    out
+}
+
+/// reserved = gen-delims / sub-delims
+fn is_reserved_or_unreserved(c: char) -> bool {
+    is_unreserved(c) || is_sub_delim(c) || matches!(c, ':' | '/' | '?' | '#' | '[' | ']' | '@')
+}
+
+/// Writes the template back out as it was parsed.
+impl Display for UriTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+fn rfc_vars() -> TemplateVars {
+    // The example variables of RFC 6570 §3.2.
+    let mut vars = TemplateVars::new();
+    vars.insert("count".into(), vec!["one", "two", "three"].into());
+    vars.insert("dom".into(), vec!["example", "com"].into());
+    vars.insert("dub".into(), "me/too".into());
+    vars.insert("hello".into(), "Hello World!".into());
+    vars.insert("half".into(), "50%".into());
+    vars.insert("var".into(), "value".into());
+    vars.insert("who".into(), "fred".into());
+    vars.insert("base".into(), "http://example.com/home/".into());
+    vars.insert("path".into(), "/foo/bar".into());
+    vars.insert("list".into(), vec!["red", "green", "blue"].into());
+    vars.insert(
+        "keys".into(),
+        vec![("semi", ";"), ("dot", "."), ("comma", ",")].into(),
+    );
+    vars.insert("v".into(), "6".into());
+    vars.insert("x".into(), "1024".into());
+    vars.insert("y".into(), "768".into());
+    vars.insert("empty".into(), "".into());
+    vars.insert("empty_keys".into(), TemplateValue::Map(vec![]));
+    vars
+}
+
+#[cfg(test)]
+fn assert_expands(vars: &TemplateVars, cases: &[(&str, &str)]) {
+    for (template, expected) in cases {
+        let template = UriTemplate::parse(template).ok().unwrap();
+        assert_eq!(&template.expand_to_string(vars), expected, "{}", template);
+    }
+}
+
+#[test]
+fn test_template_levels_1_and_2() {
+    assert_expands(
+        &rfc_vars(),
+        &[
+            ("{var}", "value"),
+            ("{hello}", "Hello%20World%21"),
+            ("{half}", "50%25"),
+            ("O{empty}X", "OX"),
+            ("O{undef}X", "OX"),
+            ("{+var}", "value"),
+            ("{+hello}", "Hello%20World!"),
+            ("{+path}/here", "/foo/bar/here"),
+            ("here?ref={+path}", "here?ref=/foo/bar"),
+            ("{#var}", "#value"),
+            ("{#hello}", "#Hello%20World!"),
+        ],
+    );
+}
+
+#[test]
+fn test_template_level_3() {
+    assert_expands(
+        &rfc_vars(),
+        &[
+            ("map?{x,y}", "map?1024,768"),
+            ("{x,hello,y}", "1024,Hello%20World%21,768"),
+            ("{+x,hello,y}", "1024,Hello%20World!,768"),
+            ("{+path,x}/here", "/foo/bar,1024/here"),
+            ("{#x,hello,y}", "#1024,Hello%20World!,768"),
+            ("X{.var}", "X.value"),
+            ("X{.x,y}", "X.1024.768"),
+            ("{/var}", "/value"),
+            ("{/var,x}/here", "/value/1024/here"),
+            ("{;x,y}", ";x=1024;y=768"),
+            ("{;x,y,empty}", ";x=1024;y=768;empty"),
+            ("{?x,y}", "?x=1024&y=768"),
+            ("{?x,y,empty}", "?x=1024&y=768&empty="),
+            ("?fixed=yes{&x}", "?fixed=yes&x=1024"),
+            ("{&x,y,empty}", "&x=1024&y=768&empty="),
+        ],
+    );
+}
+
+#[test]
+fn test_template_level_4() {
+    assert_expands(
+        &rfc_vars(),
+        &[
+            ("{var:3}", "val"),
+            ("{var:30}", "value"),
+            ("{list}", "red,green,blue"),
+            ("{list*}", "red,green,blue"),
+            ("{keys}", "semi,%3B,dot,.,comma,%2C"),
+            ("{keys*}", "semi=%3B,dot=.,comma=%2C"),
+            ("{+path:6}/here", "/foo/b/here"),
+            ("{+keys*}", "semi=;,dot=.,comma=,"),
+            ("{#path:6}/here", "#/foo/b/here"),
+            ("X{.list*}", "X.red.green.blue"),
+            ("{/var:1,var}", "/v/value"),
+            ("{/list*,path:4}", "/red/green/blue/%2Ffoo"),
+            ("{;hello:5}", ";hello=Hello"),
+            ("{;list}", ";list=red,green,blue"),
+            ("{;list*}", ";list=red;list=green;list=blue"),
+            ("{;keys*}", ";semi=%3B;dot=.;comma=%2C"),
+            ("{?var:3}", "?var=val"),
+            ("{?list*}", "?list=red&list=green&list=blue"),
+            ("{?keys}", "?keys=semi,%3B,dot,.,comma,%2C"),
+            ("{&keys*}", "&semi=%3B&dot=.&comma=%2C"),
+            ("X{.empty_keys*}", "X"),
+        ],
+    );
+}
+
+#[test]
+fn test_template_parse_errors() {
+    assert_eq!(
+        UriTemplate::parse("/users/{id").err(),
+        Some(TemplateError::UnclosedExpression { offset: 7 })
+    );
+    assert_eq!(
+        UriTemplate::parse("/users/{=id}").err(),
+        Some(TemplateError::InvalidOperator {
+            offset: 8,
+            found: '='
+        })
+    );
+    assert_eq!(
+        UriTemplate::parse("{a,b-c}").err(),
+        Some(TemplateError::InvalidVarname {
+            offset: 4,
+            found: '-'
+        })
+    );
+    assert_eq!(
+        UriTemplate::parse("{var:0}").err(),
+        Some(TemplateError::InvalidPrefix { offset: 5 })
+    );
+    assert_eq!(
+        UriTemplate::parse("/a b").err(),
+        Some(TemplateError::InvalidLiteral {
+            offset: 2,
+            found: ' '
+        })
+    );
+}
+
+#[test]
+fn test_template_expand_to_uri() {
+    let mut vars = TemplateVars::new();
+    vars.insert("id".into(), "42".into());
+    vars.insert("fields".into(), vec!["name", "e mail"].into());
+
+    let template = UriTemplate::parse("https://telemakos.io/users/{id}{?fields*}")
+        .ok()
+        .unwrap();
+    let uri = template.expand(&vars).ok().unwrap();
+    assert_eq!(uri.path(), "/users/42");
+    assert_eq!(
+        uri.query_pairs().ok().unwrap().get_all("fields"),
+        vec!["name", "e mail"]
+    );
+
+    let base = Uri::parse_tokens(&mut Tokenizer::new(String::from("http://telemakos.io/v1/")))
+        .ok()
+        .unwrap();
+    let route = UriTemplate::parse("users/{id}").ok().unwrap();
+    assert_eq!(
+        route.expand_against(&base, &vars).ok().unwrap().to_string(),
+        "http://telemakos.io/v1/users/42"
+    );
+    assert_eq!(route.variables().collect::<Vec<_>>(), vec!["id"]);
+}
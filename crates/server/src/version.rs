@@ -0,0 +1,104 @@
+use std::fmt::Display;
+
+use crate::RequestError;
+
+/// The HTTP versions the server speaks, RFC 9112 §2.3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HttpVersion {
+    Http10,
+    Http11,
+}
+
+impl HttpVersion {
+    /// Parses the HTTP-version of a request line.
+    ///
+    /// HTTP-version = HTTP-name "/" DIGIT "." DIGIT
+    ///
+    /// A later 1.x minor version is read as 1.1, the highest this server
+    /// implements, RFC 9110 §2.5. Any major version but 1 is
+    /// [`RequestError::UnsupportedVersion`].
+    pub fn parse(bytes: &[u8]) -> Result<Self, RequestError> {
+        let (major, minor) = match bytes {
+            [b'H', b'T', b'T', b'P', b'/', major, b'.', minor]
+                if major.is_ascii_digit() && minor.is_ascii_digit() =>
+            {
+                (major - b'0', minor - b'0')
+            }
+            _ => return Err(RequestError::InvalidVersion { offset: 0 }),
+        };
+
+        match (major, minor) {
+            (1, 0) => Ok(HttpVersion::Http10),
+            (1, _) => Ok(HttpVersion::Http11),
+            _ => Err(RequestError::UnsupportedVersion { major, minor }),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpVersion::Http10 => "HTTP/1.0",
+            HttpVersion::Http11 => "HTTP/1.1",
+        }
+    }
+
+    /// Whether a connection stays open after a message without a
+    /// Connection field. HTTP/1.0 closes unless keep-alive is asked for,
+    /// RFC 9112 §9.3.
+    pub fn keeps_alive_by_default(&self) -> bool {
+        *self >= HttpVersion::Http11
+    }
+
+    /// Whether the chunked transfer coding may be used. HTTP/1.0 has no
+    /// Transfer-Encoding at all, RFC 9112 §6.1.
+    pub fn supports_chunked(&self) -> bool {
+        *self >= HttpVersion::Http11
+    }
+
+    /// Whether a request must carry a Host field, RFC 9112 §3.2.
+    pub fn requires_host(&self) -> bool {
+        *self >= HttpVersion::Http11
+    }
+}
+
+impl Display for HttpVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[test]
+fn test_version_parse() {
+    assert_eq!(
+        HttpVersion::parse(b"HTTP/1.0").ok(),
+        Some(HttpVersion::Http10)
+    );
+    assert_eq!(
+        HttpVersion::parse(b"HTTP/1.1").ok(),
+        Some(HttpVersion::Http11)
+    );
+    assert_eq!(
+        HttpVersion::parse(b"HTTP/1.9").ok(),
+        Some(HttpVersion::Http11)
+    );
+    assert_eq!(
+        HttpVersion::parse(b"HTTP/2.0").err(),
+        Some(RequestError::UnsupportedVersion { major: 2, minor: 0 })
+    );
+    assert_eq!(
+        HttpVersion::parse(b"HTTP/0.9").err(),
+        Some(RequestError::UnsupportedVersion { major: 0, minor: 9 })
+    );
+    assert_eq!(
+        HttpVersion::parse(b"HTTP/1.10").err(),
+        Some(RequestError::InvalidVersion { offset: 0 })
+    );
+}
+
+#[test]
+fn test_version_semantics() {
+    assert!(!HttpVersion::Http10.keeps_alive_by_default());
+    assert!(!HttpVersion::Http10.supports_chunked());
+    assert!(!HttpVersion::Http10.requires_host());
+    assert!(HttpVersion::Http11.keeps_alive_by_default());
+    assert_eq!(HttpVersion::Http10.to_string(), "HTTP/1.0");
+}
@@ -0,0 +1,178 @@
+//! Property tests for the URI and request parsers. Both read untrusted bytes off
+//! the network, so none of these may panic for any input.
+
+use proptest::prelude::*;
+use server::{
+    domain_to_ascii, domain_to_unicode, percent_decode, Host, HttpRequest, HttpSchemeEnum, Query,
+    RequestTarget, Tokenizer, Uri, UriBuilder,
+};
+
+fn parse(input: &str) -> Option<Uri> {
+    let mut tokenizer = Tokenizer::new(String::from(input));
+    Uri::parse_tokens(&mut tokenizer).ok()
+}
+
+/// Strings made mostly of URI delimiters and escapes, which reach far more
+/// of the tokenizer than uniformly random text.
+fn uri_like() -> impl Strategy<Value = String> {
+    let prefix = prop_oneof![
+        Just(""),
+        Just("http://"),
+        Just("https://"),
+        Just("//"),
+        Just("/"),
+        Just("http:"),
+        Just("https://user:pass@"),
+        Just("http://["),
+        Just("WS://"),
+        Just("file://"),
+        Just("urn:/.//"),
+    ];
+    (prefix, "[a-zA-Z0-9:/?#\\[\\]@!$&'()*+,;=%._~ é-]{0,48}")
+        .prop_map(|(prefix, rest)| format!("{}{}", prefix, rest))
+}
+
+fn segment() -> impl Strategy<Value = String> {
+    "\\PC{0,12}"
+}
+
+proptest! {
+    #[test]
+    fn tokenizer_never_panics(input in "\\PC{0,64}") {
+        let mut tokenizer = Tokenizer::new(input);
+        let _ = tokenizer.tokens();
+        let _ = tokenizer.next();
+        let _ = tokenizer.to_string();
+    }
+
+    #[test]
+    fn tokenizer_never_panics_on_bytes(bytes in proptest::collection::vec(any::<u8>(), 0..64)) {
+        let mut tokenizer = Tokenizer::new(String::from_utf8_lossy(&bytes).into_owned());
+        let _ = tokenizer.tokens();
+    }
+
+    #[test]
+    fn request_target_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..64), input in uri_like()) {
+        let _ = RequestTarget::parse(&bytes);
+        if let Ok(target) = RequestTarget::parse(input.as_bytes()) {
+            let _ = target.to_string();
+        }
+    }
+
+    #[test]
+    fn idna_round_trips(labels in proptest::collection::vec("[a-zà-öø-ÿ0-9]{1,20}", 1..4)) {
+        let domain = labels.join(".");
+        if let Ok(ascii) = domain_to_ascii(&domain) {
+            prop_assert!(ascii.is_ascii());
+            prop_assert_eq!(domain_to_unicode(&ascii), domain);
+        }
+    }
+
+    #[test]
+    fn parse_never_panics(input in uri_like()) {
+        let _ = parse(&input);
+        let _ = Host::parse(&input);
+        let _ = Query::parse(&input);
+        let _ = percent_decode(&input);
+    }
+
+    #[test]
+    fn token_locations_slice_the_input(input in uri_like()) {
+        let mut tokenizer = Tokenizer::new(input.clone());
+        if let Ok(tokens) = tokenizer.tokens() {
+            for token in tokens {
+                let location = token.location();
+                prop_assert!(location.start() <= location.end());
+                prop_assert!(input.get(location.start()..location.end()).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn display_round_trips(input in uri_like()) {
+        if let Some(uri) = parse(&input) {
+            let reparsed = parse(&uri.to_string());
+            prop_assert_eq!(reparsed, Some(uri));
+        }
+    }
+
+    #[test]
+    fn normalize_is_idempotent(input in uri_like()) {
+        if let Some(uri) = parse(&input) {
+            let normalized = uri.normalize();
+            prop_assert_eq!(normalized.normalize(), normalized.clone());
+            prop_assert!(!normalized.has_authority() || normalized.path().starts_with('/'));
+            prop_assert!(!normalized.path_segments().any(|s| s == ".."));
+        }
+    }
+
+    #[test]
+    fn join_never_panics(reference in uri_like()) {
+        for base in ["http://a/b/c/d;p?q", "urn:a"] {
+            let base = parse(base).unwrap();
+            if let Ok(joined) = base.join(&reference) {
+                prop_assert!(!joined.path_segments().any(|s| s == ".." || s == "."));
+                prop_assert_eq!(parse(&joined.to_string()), Some(joined));
+            }
+        }
+    }
+
+    #[test]
+    fn builder_output_parses_back(
+        https in any::<bool>(),
+        user in proptest::option::of(segment()),
+        host in "[a-z0-9-]{1,16}(\\.[a-z0-9-]{1,8}){0,2}",
+        port in proptest::option::of(any::<u16>()),
+        segments in proptest::collection::vec(segment(), 0..4),
+        pairs in proptest::collection::vec((segment(), segment()), 0..4),
+        fragment in proptest::option::of(segment()),
+    ) {
+        let scheme = if https { HttpSchemeEnum::HTTPS } else { HttpSchemeEnum::HTTP };
+        let mut builder = UriBuilder::new().scheme(scheme).host(&host);
+        if let Some(user) = &user {
+            builder = builder.userinfo(user, None);
+        }
+        if let Some(port) = port {
+            builder = builder.port(port);
+        }
+        builder = builder.path_segments(segments.iter().map(String::as_str));
+        for (key, value) in &pairs {
+            builder = builder.query_pair(key, value);
+        }
+        if let Some(fragment) = &fragment {
+            builder = builder.fragment(fragment);
+        }
+
+        let uri = builder.build().unwrap();
+        prop_assert_eq!(uri.username(), user.as_deref());
+        prop_assert_eq!(uri.path_segments().collect::<Vec<_>>(), segments);
+        let query = uri.query_pairs().unwrap();
+        let parsed_pairs: Vec<(String, String)> = query
+            .iter()
+            .map(|(k, v)| (String::from(k), String::from(v)))
+            .collect();
+        prop_assert_eq!(parsed_pairs, pairs);
+        prop_assert_eq!(parse(&uri.to_string()), Some(uri));
+    }
+}
+
+proptest! {
+    #[test]
+    fn request_never_panics(
+        bytes in proptest::collection::vec(any::<u8>(), 0..128),
+        head in "[A-Z]{1,7} /[a-z?=]{0,8} HTTP/1\\.[01]\r?\n([A-Za-z-]{1,8}:[ \t]?[ -~]{0,8}\r?\n){0,4}\r?\n",
+    ) {
+        let _ = HttpRequest::parse(&bytes);
+        if let Ok((_, consumed)) = HttpRequest::parse(head.as_bytes()) {
+            prop_assert!(consumed <= head.len());
+        }
+    }
+}
+
+#[test]
+fn double_slash_path_round_trips() {
+    let joined = parse("urn:a").unwrap().join("/.//g").unwrap();
+    assert_eq!(joined.path(), "//g");
+    assert_eq!(joined.to_string(), "urn:/.//g");
+    assert_eq!(parse(&joined.to_string()), Some(joined));
+}